@@ -0,0 +1,67 @@
+use std::any::Any;
+use std::sync::Arc;
+
+/// The value being dragged by a drag_source, kept in egui memory until the pointer is released
+#[derive(Clone)]
+pub(crate) struct DragPayload(Arc<dyn Any + Send + Sync>);
+
+impl DragPayload {
+    fn id() -> egui::Id {
+        egui::Id::new("egui_nodes_drag_payload")
+    }
+
+    /// Removes the payload from egui memory if there is one
+    pub(crate) fn take(ctx: &egui::CtxRef) -> Option<Self> {
+        let mut memory = ctx.memory();
+        let payload = memory.data.get_temp::<Self>(Self::id());
+        memory.data.remove::<Self>(Self::id());
+        payload
+    }
+
+    pub(crate) fn downcast_ref<P: Any>(&self) -> Option<&P> {
+        self.0.downcast_ref()
+    }
+}
+
+/// Makes the contents draggable, dropping them on a Context reports payload through Context::dropped_payload
+/// ``` rust
+/// # use egui_nodes::{drag_source, Context, NodeConstructor, LinkArgs};
+/// struct Template(&'static str);
+///
+/// fn sidebar_and_editor(ctx: &mut Context, ui: &mut egui::Ui) {
+///     drag_source(ui, egui::Id::new("add"), Template("add"), |ui| {
+///         ui.label("Add");
+///     });
+///     ctx.show(Vec::<NodeConstructor>::new(), Vec::<(usize, usize, usize, LinkArgs)>::new(), ui);
+///     if let Some((template, pos)) = ctx.dropped_payload::<Template>() {
+///         println!("add a {} node at {:?}", template.0, pos);
+///     }
+/// }
+/// ```
+pub fn drag_source<P: Any + Send + Sync>(
+    ui: &mut egui::Ui,
+    id: egui::Id,
+    payload: P,
+    add_contents: impl FnOnce(&mut egui::Ui),
+) -> egui::Response {
+    if ui.memory().is_being_dragged(id) {
+        ui.output().cursor_icon = egui::CursorIcon::Grabbing;
+        // the payload of the last frame the button was held down is dropped on release
+        if ui.input().pointer.any_down() {
+            ui.memory().data.insert_temp(DragPayload::id(), DragPayload(Arc::new(payload)));
+        }
+        let layer_id = egui::LayerId::new(egui::Order::Tooltip, id);
+        let response = ui.with_layer_id(layer_id, add_contents).response;
+        if let Some(pointer_pos) = ui.input().pointer.interact_pos() {
+            ui.ctx().translate_layer(layer_id, pointer_pos - response.rect.center());
+        }
+        response
+    } else {
+        let response = ui.scope(add_contents).response;
+        let response = ui.interact(response.rect, id, egui::Sense::drag());
+        if response.hovered() {
+            ui.output().cursor_icon = egui::CursorIcon::Grab;
+        }
+        response
+    }
+}
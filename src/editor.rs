@@ -0,0 +1,416 @@
+use super::*;
+
+/// The fraction of the editor size used by the minimap
+const MINIMAP_SIZE_FRACTION: f32 = 0.2;
+/// The space between the minimap and the edge of the editor
+const MINIMAP_MARGIN: f32 = 8.0;
+
+type ContextMenu<'c, A> = Box<dyn FnOnce(&mut egui::Ui, A) + 'c>;
+type Palette<'c, T> = Box<dyn FnOnce(&Context<T>, &mut egui::Ui) + 'c>;
+
+/// Shows a Context with options that only apply to a single frame.
+/// Can be added like any other egui widget, or shown with NodeEditor::show to get a NodeEditorResponse
+/// ``` rust
+/// # use egui_nodes::{Context, NodeConstructor, NodeEditor, LinkArgs};
+/// fn editor(ctx: &mut Context, links: &[(usize, usize)], ui: &mut egui::Ui) {
+///     let nodes = vec![
+///         NodeConstructor::new(0, Default::default())
+///             .with_output_attribute(0, Default::default(), |ui| ui.label("Output")),
+///         NodeConstructor::new(1, Default::default())
+///             .with_input_attribute(1, Default::default(), |ui| ui.label("Input")),
+///     ];
+///     let links = links.iter().enumerate().map(|(i, (start, end))| (i, *start, *end, LinkArgs::default()));
+///     let response = NodeEditor::new(ctx, nodes, links)
+///         .size(egui::vec2(400.0, 300.0))
+///         .minimap(true)
+///         .show(ui);
+///     for event in response.events {
+///         println!("{:?}", event);
+///     }
+/// }
+/// ```
+#[must_use = "You should call .show() or add the NodeEditor to a Ui"]
+pub struct NodeEditor<'c, T, N, L> {
+    ctx: &'c mut Context<T>,
+    nodes: N,
+    links: L,
+    size: Option<egui::Vec2>,
+    interaction_mode: Option<InteractionMode>,
+    background: Option<egui::Color32>,
+    grid: Option<bool>,
+    minimap: bool,
+    canvas_context_menu: Option<ContextMenu<'c, egui::Pos2>>,
+    node_context_menu: Option<ContextMenu<'c, T>>,
+    link_context_menu: Option<ContextMenu<'c, T>>,
+    pin_context_menu: Option<ContextMenu<'c, T>>,
+    palette: Option<Palette<'c, T>>,
+}
+
+impl<'c, T, N, L> NodeEditor<'c, T, N, L> {
+    /// Takes the same nodes and links as Context::show
+    pub fn new(ctx: &'c mut Context<T>, nodes: N, links: L) -> Self {
+        Self {
+            ctx,
+            nodes,
+            links,
+            size: None,
+            interaction_mode: None,
+            background: None,
+            grid: None,
+            minimap: false,
+            canvas_context_menu: None,
+            node_context_menu: None,
+            link_context_menu: None,
+            pin_context_menu: None,
+            palette: None,
+        }
+    }
+
+    /// The size of the editor, by default it fills the available space
+    pub fn size(mut self, size: egui::Vec2) -> Self {
+        self.size.replace(size);
+        self
+    }
+
+    /// Limit the interactions for this frame, by default Context::interaction_mode is used
+    pub fn interaction_mode(mut self, mode: InteractionMode) -> Self {
+        self.interaction_mode.replace(mode);
+        self
+    }
+
+    /// Disable link creation, link detaching and node dragging, nodes and links can still be selected.
+    /// false clears the override so Context::interaction_mode is used again
+    pub fn read_only(mut self, read_only: bool) -> Self {
+        self.interaction_mode = if read_only {
+            Some(InteractionMode::SelectionOnly)
+        } else {
+            None
+        };
+        self
+    }
+
+    /// The color behind the grid, by default ColorStyle::GridBackground
+    pub fn background(mut self, background: egui::Color32) -> Self {
+        self.background.replace(background);
+        self
+    }
+
+    /// Show or hide the grid lines, by default StyleFlags::GridLines decides
+    pub fn grid(mut self, grid: bool) -> Self {
+        self.grid.replace(grid);
+        self
+    }
+
+    /// Show a minimap of the whole graph in the corner of the editor, click or drag on it to pan
+    pub fn minimap(mut self, minimap: bool) -> Self {
+        self.minimap = minimap;
+        self
+    }
+
+    /// The contents of the menu opened by right-clicking empty space, given the clicked position in grid space.
+    /// Menus stay open until egui::Ui::close_menu is called or the user clicks elsewhere
+    pub fn canvas_context_menu(
+        mut self,
+        menu: impl FnOnce(&mut egui::Ui, egui::Pos2) + 'c,
+    ) -> Self {
+        self.canvas_context_menu.replace(Box::new(menu));
+        self
+    }
+
+    /// The contents of the menu opened by right-clicking a node, given the node id
+    pub fn node_context_menu(mut self, menu: impl FnOnce(&mut egui::Ui, T) + 'c) -> Self {
+        self.node_context_menu.replace(Box::new(menu));
+        self
+    }
+
+    /// The contents of the menu opened by right-clicking a link, given the link id
+    pub fn link_context_menu(mut self, menu: impl FnOnce(&mut egui::Ui, T) + 'c) -> Self {
+        self.link_context_menu.replace(Box::new(menu));
+        self
+    }
+
+    /// The contents of the menu opened by right-clicking a pin, given the pin id
+    pub fn pin_context_menu(mut self, menu: impl FnOnce(&mut egui::Ui, T) + 'c) -> Self {
+        self.pin_context_menu.replace(Box::new(menu));
+        self
+    }
+
+    /// Show a NodePalette over the editor, the chosen entry is taken with NodePalette::take_selection
+    pub fn palette<K: Clone>(mut self, palette: &'c mut NodePalette<K, T>) -> Self
+    where
+        T: Hash + Eq + Clone,
+    {
+        self.palette.replace(Box::new(move |ctx, ui| palette.show(ctx, ui)));
+        self
+    }
+}
+
+impl<'c, T: Hash + Eq + Clone, N, L> NodeEditor<'c, T, N, L> {
+    pub fn show<'a>(self, ui: &mut egui::Ui) -> NodeEditorResponse<T>
+    where
+        N: IntoIterator<Item = NodeConstructor<'a, T>>,
+        L: IntoIterator<Item = (T, T, T, LinkArgs)>,
+    {
+        let NodeEditor {
+            ctx,
+            nodes,
+            links,
+            size,
+            interaction_mode,
+            background,
+            grid,
+            minimap,
+            canvas_context_menu,
+            node_context_menu,
+            link_context_menu,
+            pin_context_menu,
+            palette,
+        } = self;
+
+        if let Some(background) = background {
+            ctx.color_style_push(ColorStyle::GridBackground, background);
+        }
+        let flags = ctx.style.flags;
+        match grid {
+            Some(true) => ctx.style.flags |= StyleFlags::GridLines as usize,
+            Some(false) => ctx.style.flags &= !(StyleFlags::GridLines as usize),
+            None => (),
+        }
+        let mode = ctx.interaction_mode;
+        if let Some(interaction_mode) = interaction_mode {
+            ctx.interaction_mode = interaction_mode;
+        }
+        ctx.minimap = minimap;
+
+        let response = match size {
+            Some(size) => ui.allocate_ui(size, |ui| ctx.show(nodes, links, ui)).inner,
+            None => ctx.show(nodes, links, ui),
+        };
+
+        let response = match ctx.context_menu_target.clone() {
+            Some(ContextMenuTarget::Canvas(pos)) => {
+                context_menu(response, canvas_context_menu, pos)
+            }
+            Some(ContextMenuTarget::Node(id)) => context_menu(response, node_context_menu, id),
+            Some(ContextMenuTarget::Link(id)) => context_menu(response, link_context_menu, id),
+            Some(ContextMenuTarget::Pin(id)) => context_menu(response, pin_context_menu, id),
+            None => response,
+        };
+        if let Some(palette) = palette {
+            palette(ctx, ui);
+        }
+
+        ctx.interaction_mode = mode;
+        ctx.minimap = false;
+        ctx.style.flags = flags;
+        if background.is_some() {
+            ctx.color_style_pop();
+        }
+
+        NodeEditorResponse {
+            response,
+            hovered_node: ctx.node_hovered(),
+            hovered_link: ctx.link_hovered(),
+            hovered_pin: ctx.pin_hovered(),
+            events: ctx.events(),
+        }
+    }
+}
+
+/// Shows the menu if there is one for the kind of element that was right-clicked
+fn context_menu<A>(
+    response: egui::Response,
+    menu: Option<ContextMenu<'_, A>>,
+    arg: A,
+) -> egui::Response {
+    match menu {
+        Some(menu) => response.context_menu(|ui| menu(ui, arg)),
+        None => response,
+    }
+}
+
+impl<'a, 'c, T, N, L> egui::Widget for NodeEditor<'c, T, N, L>
+where
+    T: Hash + Eq + Clone,
+    N: IntoIterator<Item = NodeConstructor<'a, T>>,
+    L: IntoIterator<Item = (T, T, T, LinkArgs)>,
+{
+    fn ui(self, ui: &mut egui::Ui) -> egui::Response {
+        self.show(ui).response
+    }
+}
+
+/// What happened in the editor during a frame shown with NodeEditor::show
+#[derive(Debug)]
+pub struct NodeEditorResponse<T> {
+    /// The response of the editor canvas
+    pub response: egui::Response,
+    pub hovered_node: Option<T>,
+    pub hovered_link: Option<T>,
+    pub hovered_pin: Option<T>,
+    pub events: Vec<NodeEditorEvent<T>>,
+}
+
+/// Changes to the links of the editor. There is at most one event of each kind per frame, except for one
+/// VariadicSlotRemoved per slot, and they are reported in a fixed order:
+/// LinkDestroyed, VariadicSlotRemoved, LinkStarted, LinkCreated, then LinkDropped
+#[derive(Debug, Clone, PartialEq)]
+pub enum NodeEditorEvent<T> {
+    /// An existing link was detached and should be removed, see Context::link_destroyed
+    LinkDestroyed { link: T },
+    /// The destroyed link was the only link of a slot of a variadic attribute, the app should remove the slot
+    /// so the later slots move down one, see Context::variadic_slots_removed
+    VariadicSlotRemoved { attribute: T, slot: usize },
+    /// A new link is being dragged from a pin, see Context::link_started
+    LinkStarted { pin: T },
+    /// A new link should be added, from an output pin to an input pin, see Context::link_created_node.
+    /// The slots are set for pins of variadic attributes, see Context::get_pin_slot
+    LinkCreated {
+        start_pin: T,
+        start_node: T,
+        end_pin: T,
+        end_node: T,
+        created_from_snap: bool,
+        start_slot: Option<usize>,
+        end_slot: Option<usize>,
+    },
+    /// A link was dropped without connecting to a pin, see Context::link_dropped
+    LinkDropped { pin: T, detached: bool },
+}
+
+impl<T: Hash + Eq + Clone> Context<T> {
+    /// The events of the last frame, in the order documented on NodeEditorEvent
+    pub(crate) fn events(&self) -> Vec<NodeEditorEvent<T>> {
+        let mut events = Vec::new();
+        if let Some(link) = self.link_destroyed() {
+            events.push(NodeEditorEvent::LinkDestroyed { link });
+        }
+        for (attribute, slot) in self.variadic_slots_removed() {
+            events.push(NodeEditorEvent::VariadicSlotRemoved { attribute, slot });
+        }
+        if let Some(pin) = self.link_started() {
+            events.push(NodeEditorEvent::LinkStarted { pin });
+        }
+        if let Some((start_pin, start_node, end_pin, end_node, created_from_snap)) =
+            self.link_created_node()
+        {
+            events.push(NodeEditorEvent::LinkCreated {
+                start_slot: self.get_pin_slot(start_pin.clone()),
+                end_slot: self.get_pin_slot(end_pin.clone()),
+                start_pin,
+                start_node,
+                end_pin,
+                end_node,
+                created_from_snap,
+            });
+        }
+        if let Some(pin) = self.link_dropped(true) {
+            let detached = self.click_interaction_state.link_creation.link_creation_type
+                == LinkCreationType::FromDetach;
+            events.push(NodeEditorEvent::LinkDropped { pin, detached });
+        }
+        events
+    }
+
+    /// Where the minimap is drawn in screen space
+    pub(crate) fn minimap_rect(&self) -> egui::Rect {
+        let canvas = self.canvas_rect_screen_space;
+        let size = canvas.size() * MINIMAP_SIZE_FRACTION;
+        egui::Rect::from_min_size(canvas.max - size - egui::Vec2::splat(MINIMAP_MARGIN), size)
+    }
+
+    /// The grid space rect covered by the minimap, which includes all nodes and the visible part of the grid,
+    /// and the scale from grid space to the minimap. None while either of them has no area
+    fn minimap_bounds(&self) -> Option<(egui::Rect, f32)> {
+        let mut bounds = egui::Rect::from_min_size(
+            self.screen_space_to_grid_space(self.canvas_rect_screen_space.min),
+            self.canvas_rect_screen_space.size(),
+        );
+        for (node, in_use) in self.nodes.pool.iter().zip(self.nodes.in_use.iter()) {
+            if *in_use {
+                bounds = bounds.union(node.layout_rect());
+            }
+        }
+        let minimap = self.minimap_rect();
+        if [bounds, minimap].iter().any(|x| x.width() <= 0.0 || x.height() <= 0.0) {
+            return None;
+        }
+        let scale = (minimap.width() / bounds.width()).min(minimap.height() / bounds.height());
+        Some((bounds, scale))
+    }
+
+    fn grid_space_to_minimap(&self, v: egui::Pos2, bounds: &egui::Rect, scale: f32) -> egui::Pos2 {
+        self.minimap_rect().center() + (v - bounds.center()) * scale
+    }
+
+    /// Pans the editor so the point of the minimap under pos is in the center of the editor
+    pub(crate) fn minimap_pan_to(&mut self, pos: egui::Pos2) {
+        let (bounds, scale) = match self.minimap_bounds() {
+            Some(x) => x,
+            None => return,
+        };
+        let target = bounds.center() + (pos - self.minimap_rect().center()) / scale;
+        self.panning =
+            self.canvas_rect_screen_space.center() - target - self.canvas_origin_screen_space;
+    }
+
+    pub(crate) fn draw_minimap(&self, ui: &mut egui::Ui) {
+        let minimap = self.minimap_rect();
+        let (bounds, scale) = match self.minimap_bounds() {
+            Some(x) => x,
+            None => return,
+        };
+        let to_minimap = |v: egui::Pos2| self.grid_space_to_minimap(v, &bounds, scale);
+        let painter = ui.painter_at(minimap);
+
+        painter.rect(
+            minimap,
+            0.0,
+            self.style.colors[ColorStyle::MiniMapBackground as usize],
+            (1.0, self.style.colors[ColorStyle::MiniMapOutline as usize]),
+        );
+
+        for (idx, link) in self.links.pool.iter().enumerate() {
+            if self.links.in_use[idx] && self.deleted_link_idx != Some(idx) {
+                let start =
+                    self.screen_space_to_grid_space(self.pins.pool[link.start_pin_index].pos);
+                let end = self.screen_space_to_grid_space(self.pins.pool[link.end_pin_index].pos);
+                painter.line_segment(
+                    [to_minimap(start), to_minimap(end)],
+                    (1.0, self.style.colors[ColorStyle::MiniMapLink as usize]),
+                );
+            }
+        }
+
+        for idx in self.node_depth_order.iter() {
+            if !self.nodes.in_use[*idx] {
+                continue;
+            }
+            let rect = self.nodes.pool[*idx].layout_rect();
+            let color = if self.selected_node_indices.contains(idx) {
+                ColorStyle::MiniMapNodeBackgroundSelected
+            } else {
+                ColorStyle::MiniMapNodeBackground
+            };
+            painter.rect_filled(
+                egui::Rect::from_min_max(to_minimap(rect.min), to_minimap(rect.max)),
+                0.0,
+                self.style.colors[color as usize],
+            );
+        }
+
+        let canvas = egui::Rect::from_min_size(
+            self.screen_space_to_grid_space(self.canvas_rect_screen_space.min),
+            self.canvas_rect_screen_space.size(),
+        );
+        painter.rect(
+            egui::Rect::from_min_max(to_minimap(canvas.min), to_minimap(canvas.max)),
+            0.0,
+            self.style.colors[ColorStyle::MiniMapCanvas as usize],
+            (
+                1.0,
+                self.style.colors[ColorStyle::MiniMapCanvasOutline as usize],
+            ),
+        );
+    }
+}
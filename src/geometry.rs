@@ -0,0 +1,402 @@
+//! Geometry used to draw and hit test links, exposed for apps that draw their own overlays
+
+/// A cubic bezier curve given by its start point, two control points and end point
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BezierCurve(
+    pub egui::Pos2,
+    pub egui::Pos2,
+    pub egui::Pos2,
+    pub egui::Pos2,
+);
+
+impl BezierCurve {
+    #[inline]
+    pub fn eval(&self, t: f32) -> egui::Pos2 {
+        let u = 1.0 - t;
+        (u * u * u * self.0.to_vec2()
+            + 3.0 * u * u * t * self.1.to_vec2()
+            + 3.0 * u * t * t * self.2.to_vec2()
+            + t * t * t * self.3.to_vec2())
+        .to_pos2()
+    }
+
+    /// The tangent of the curve at t
+    #[inline]
+    pub fn derivative(&self, t: f32) -> egui::Vec2 {
+        let u = 1.0 - t;
+        3.0 * u * u * (self.1 - self.0)
+            + 6.0 * u * t * (self.2 - self.1)
+            + 3.0 * t * t * (self.3 - self.2)
+    }
+
+    /// de Casteljau subdivision at t
+    pub fn split(&self, t: f32) -> (Self, Self) {
+        let lerp = |a: egui::Pos2, b: egui::Pos2| a + (b - a) * t;
+        let p01 = lerp(self.0, self.1);
+        let p12 = lerp(self.1, self.2);
+        let p23 = lerp(self.2, self.3);
+        let p012 = lerp(p01, p12);
+        let p123 = lerp(p12, p23);
+        let mid = lerp(p012, p123);
+        (
+            BezierCurve(self.0, p01, p012, mid),
+            BezierCurve(mid, p123, p23, self.3),
+        )
+    }
+
+    /// Split the curve until every piece is within tolerance of a straight line,
+    /// pushing the end point of each piece
+    pub fn flatten(&self, tolerance: f32, points: &mut Vec<egui::Pos2>) {
+        const MAX_DEPTH: usize = 16;
+        self.flatten_recursive(tolerance.max(0.01), MAX_DEPTH, points);
+    }
+
+    fn flatten_recursive(&self, tolerance: f32, depth: usize, points: &mut Vec<egui::Pos2>) {
+        if depth == 0 || self.is_flat(tolerance) {
+            points.push(self.3);
+            return;
+        }
+        let (first, second) = self.split(0.5);
+        first.flatten_recursive(tolerance, depth - 1, points);
+        second.flatten_recursive(tolerance, depth - 1, points);
+    }
+
+    /// Checks if the control points are within tolerance of the segment between the end points,
+    /// which bounds the distance of the whole curve from that segment
+    #[inline]
+    fn is_flat(&self, tolerance: f32) -> bool {
+        let tolerance_sq = tolerance * tolerance;
+        let distance_sq =
+            |p: egui::Pos2| p.distance_sq(segment_closest_point(&self.0, &self.3, &p));
+        distance_sq(self.1) <= tolerance_sq && distance_sq(self.2) <= tolerance_sq
+    }
+
+    /// The smallest rect containing the curve
+    pub fn bounding_rect(&self) -> egui::Rect {
+        let mut rect = egui::Rect::from_two_pos(self.0, self.3);
+        let mut roots = Vec::new();
+        for axis in 0..2 {
+            // the derivative of one axis in power form, its roots are the extremes of the curve
+            let [p0, p1, p2, p3] = self.axis(axis);
+            let derivative = [
+                p1 - p0,
+                2.0 * (p0 - 2.0 * p1 + p2),
+                3.0 * p1 - p0 - 3.0 * p2 + p3,
+            ];
+            roots.clear();
+            polynomial_roots(&derivative, &mut roots);
+            for t in roots.iter() {
+                rect.extend_with(self.eval(*t as f32));
+            }
+        }
+        rect
+    }
+
+    /// The point on the curve closest to p and the curve parameter of that point
+    pub fn closest_point(&self, p: &egui::Pos2) -> (f32, egui::Pos2) {
+        // the closest point is an end point or a root of (B(t) - p) . B'(t), a quintic
+        let [x, y] = [self.power_form(0), self.power_form(1)];
+        let (px, py) = (p.x as f64, p.y as f64);
+        let dot = |a: [f64; 4], p: f64| {
+            let c = [a[0] - p, a[1], a[2], a[3]];
+            let d = [a[1], 2.0 * a[2], 3.0 * a[3]];
+            let mut product = [0.0; 6];
+            for (i, ci) in c.iter().enumerate() {
+                for (j, dj) in d.iter().enumerate() {
+                    product[i + j] += ci * dj;
+                }
+            }
+            product
+        };
+        let (dx, dy) = (dot(x, px), dot(y, py));
+        let mut quintic = [0.0; 6];
+        for i in 0..6 {
+            quintic[i] = dx[i] + dy[i];
+        }
+
+        let mut candidates = vec![0.0, 1.0];
+        polynomial_roots(&quintic, &mut candidates);
+        candidates
+            .into_iter()
+            .map(|t| (t as f32, self.eval(t as f32)))
+            .min_by(|a, b| {
+                p.distance_sq(a.1)
+                    .partial_cmp(&p.distance_sq(b.1))
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            })
+            .unwrap()
+    }
+
+    #[inline]
+    pub fn distance(&self, p: &egui::Pos2) -> f32 {
+        p.distance(self.closest_point(p).1)
+    }
+
+    /// Checks if any part of the curve is inside of rect
+    pub fn overlaps_rect(&self, rect: &egui::Rect) -> bool {
+        if rect.contains(self.0) || rect.contains(self.3) {
+            return true;
+        }
+        if !rect.intersects(self.bounding_rect()) {
+            return false;
+        }
+
+        // both ends are outside, so the curve has to cross one of the edges
+        let mut roots = Vec::new();
+        for axis in 0..2 {
+            let other = 1 - axis;
+            let edges = [rect.min[axis], rect.max[axis]];
+            let range = rect.min[other]..=rect.max[other];
+            let coefficients = self.power_form(axis);
+            for edge in edges.iter() {
+                let mut shifted = coefficients;
+                shifted[0] -= *edge as f64;
+                roots.clear();
+                polynomial_roots(&shifted, &mut roots);
+                if roots.iter().any(|t| range.contains(&self.eval(*t as f32)[other])) {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    /// The control points along one axis
+    #[inline]
+    fn axis(&self, axis: usize) -> [f64; 4] {
+        [
+            self.0[axis] as f64,
+            self.1[axis] as f64,
+            self.2[axis] as f64,
+            self.3[axis] as f64,
+        ]
+    }
+
+    /// The coefficients of one axis of the curve as a polynomial in t, lowest degree first
+    #[inline]
+    fn power_form(&self, axis: usize) -> [f64; 4] {
+        let [p0, p1, p2, p3] = self.axis(axis);
+        [
+            p0,
+            3.0 * (p1 - p0),
+            3.0 * (p0 - 2.0 * p1 + p2),
+            p3 - p0 + 3.0 * (p1 - p2),
+        ]
+    }
+}
+
+/// The point on the segment from a to b closest to p
+#[inline]
+pub fn segment_closest_point(a: &egui::Pos2, b: &egui::Pos2, p: &egui::Pos2) -> egui::Pos2 {
+    let ap = *p - *a;
+    let ab_dir = *b - *a;
+    let dot = ap.x * ab_dir.x + ap.y * ab_dir.y;
+    if dot <= 0.0 {
+        return *a;
+    }
+    let ab_len_sqr = ab_dir.x * ab_dir.x + ab_dir.y * ab_dir.y;
+    if dot >= ab_len_sqr {
+        return *b;
+    }
+    *a + ab_dir * dot / ab_len_sqr
+}
+
+/// Evaluates the implicit equation of the line through p1 and p2 at p.
+/// The sign tells which side of the line p is on, zero is on the line
+#[inline]
+fn eval_implicit_line_eq(p1: &egui::Pos2, p2: &egui::Pos2, p: &egui::Pos2) -> f32 {
+    (p2.y - p1.y) * p.x + (p1.x - p2.x) * p.y + (p2.x * p1.y - p1.x * p2.y)
+}
+
+/// Checks if any part of the segment from p1 to p2 is inside of rect
+pub fn segment_overlaps_rect(rect: &egui::Rect, p1: &egui::Pos2, p2: &egui::Pos2) -> bool {
+    if rect.contains(*p1) || rect.contains(*p2) {
+        return true;
+    }
+
+    if (p1.x < rect.min.x && p2.x < rect.min.x)
+        || (p1.x > rect.max.x && p2.x > rect.max.x)
+        || (p1.y < rect.min.y && p2.y < rect.min.y)
+        || (p1.y > rect.max.y && p2.y > rect.max.y)
+    {
+        return false;
+    }
+
+    // the bounding boxes overlap, so the segment is inside if the line separates the corners
+    let corners = [
+        rect.left_bottom(),
+        rect.left_top(),
+        rect.right_bottom(),
+        rect.right_top(),
+    ];
+    let mut below = false;
+    let mut above = false;
+    for corner in corners.iter() {
+        let value = eval_implicit_line_eq(p1, p2, corner);
+        below |= value <= 0.0;
+        above |= value >= 0.0;
+    }
+    below && above
+}
+
+/// Pushes the real roots in [0, 1] of the polynomial with the given coefficients, lowest degree first.
+/// Roots of the derivative split the interval into monotonic pieces that contain at most one root each
+fn polynomial_roots(coefficients: &[f64], roots: &mut Vec<f64>) {
+    let scale = coefficients.iter().fold(0.0f64, |acc, x| acc.max(x.abs()));
+    if scale == 0.0 {
+        return;
+    }
+    let mut degree = coefficients.len() - 1;
+    while degree > 0 && coefficients[degree].abs() <= scale * 1e-12 {
+        degree -= 1;
+    }
+    let coefficients = &coefficients[..=degree];
+    let eval = |t: f64| coefficients.iter().rev().fold(0.0, |acc, c| acc * t + c);
+
+    match degree {
+        0 => (),
+        1 => {
+            let t = -coefficients[0] / coefficients[1];
+            if (0.0..=1.0).contains(&t) {
+                roots.push(t);
+            }
+        }
+        _ => {
+            let derivative: Vec<f64> =
+                coefficients.iter().enumerate().skip(1).map(|(i, c)| i as f64 * c).collect();
+            let mut bounds = vec![0.0];
+            polynomial_roots(&derivative, &mut bounds);
+            bounds.push(1.0);
+            bounds.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+
+            let tolerance = scale * 1e-9;
+            for (i, window) in bounds.windows(2).enumerate() {
+                let (mut lo, mut hi) = (window[0], window[1]);
+                let (mut f_lo, f_hi) = (eval(lo), eval(hi));
+                // a root can only touch zero at a turning point without changing sign
+                if i == 0 && f_lo.abs() <= tolerance {
+                    roots.push(lo);
+                }
+                if f_hi.abs() <= tolerance {
+                    roots.push(hi);
+                    continue;
+                }
+                if f_lo.abs() <= tolerance || f_lo.signum() == f_hi.signum() {
+                    continue;
+                }
+                for _ in 0..64 {
+                    let mid = 0.5 * (lo + hi);
+                    let f_mid = eval(mid);
+                    if f_mid.signum() == f_lo.signum() {
+                        lo = mid;
+                        f_lo = f_mid;
+                    } else {
+                        hi = mid;
+                    }
+                }
+                roots.push(0.5 * (lo + hi));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    const SAMPLES: usize = 2000;
+
+    fn pos() -> impl Strategy<Value = egui::Pos2> {
+        (-200.0f32..200.0, -200.0f32..200.0).prop_map(|(x, y)| egui::pos2(x, y))
+    }
+
+    fn rect() -> impl Strategy<Value = egui::Rect> {
+        (pos(), 1.0f32..150.0, 1.0f32..150.0)
+            .prop_map(|(min, w, h)| egui::Rect::from_min_size(min, egui::vec2(w, h)))
+    }
+
+    fn bezier() -> impl Strategy<Value = BezierCurve> {
+        (pos(), pos(), pos(), pos()).prop_map(|(p0, p1, p2, p3)| BezierCurve(p0, p1, p2, p3))
+    }
+
+    fn samples(bezier: &BezierCurve) -> impl Iterator<Item = egui::Pos2> + '_ {
+        (0..=SAMPLES).map(move |i| bezier.eval(i as f32 / SAMPLES as f32))
+    }
+
+    fn distance_to_rect(rect: &egui::Rect, p: egui::Pos2) -> f32 {
+        p.distance(rect.clamp(p))
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(500))]
+
+        #[test]
+        fn segment_overlaps_rect_matches_sampling(rect in rect(), p1 in pos(), p2 in pos()) {
+            let step = p1.distance(p2) / SAMPLES as f32;
+            let closest = (0..=SAMPLES)
+                .map(|i| distance_to_rect(&rect, p1 + (p2 - p1) * (i as f32 / SAMPLES as f32)))
+                .fold(f32::MAX, f32::min);
+            let overlaps = segment_overlaps_rect(&rect, &p1, &p2);
+            if closest == 0.0 {
+                prop_assert!(overlaps);
+            } else if overlaps {
+                prop_assert!(closest <= step + 1e-3);
+            }
+        }
+
+        #[test]
+        fn closest_point_matches_sampling(bezier in bezier(), p in pos()) {
+            let sampled = samples(&bezier).map(|x| p.distance(x)).fold(f32::MAX, f32::min);
+            let (t, closest) = bezier.closest_point(&p);
+            prop_assert!((0.0..=1.0).contains(&t));
+            prop_assert!(closest.distance(bezier.eval(t)) < 1e-3);
+            prop_assert!(p.distance(closest) <= sampled + 1e-2);
+        }
+
+        #[test]
+        fn bounding_rect_is_tight(bezier in bezier()) {
+            let rect = bezier.bounding_rect();
+            let sampled = samples(&bezier)
+                .fold(egui::Rect::from_two_pos(bezier.0, bezier.3), |acc, x| {
+                    acc.union(egui::Rect::from_min_max(x, x))
+                });
+            prop_assert!(rect.expand(1e-2).contains_rect(sampled));
+            prop_assert!(sampled.expand(1e-1).contains_rect(rect));
+        }
+
+        #[test]
+        fn bezier_overlaps_rect_matches_sampling(bezier in bezier(), rect in rect()) {
+            let closest =
+                samples(&bezier).map(|x| distance_to_rect(&rect, x)).fold(f32::MAX, f32::min);
+            let overlaps = bezier.overlaps_rect(&rect);
+            if closest == 0.0 {
+                prop_assert!(overlaps);
+            } else if overlaps {
+                prop_assert!(closest < 1.0);
+            }
+        }
+
+        #[test]
+        fn flattened_curve_is_within_tolerance(bezier in bezier(), tolerance in 0.1f32..2.0) {
+            let mut points = vec![bezier.0];
+            bezier.flatten(tolerance, &mut points);
+            for p in samples(&bezier).step_by(10) {
+                let distance = points
+                    .windows(2)
+                    .map(|x| p.distance(segment_closest_point(&x[0], &x[1], &p)))
+                    .fold(f32::MAX, f32::min);
+                prop_assert!(distance <= tolerance + 1e-2);
+            }
+        }
+    }
+
+    #[test]
+    fn segment_crossing_rect_without_end_points_inside() {
+        let rect = egui::Rect::from_min_max(egui::pos2(10.0, 10.0), egui::pos2(20.0, 20.0));
+        let crossing = (egui::pos2(0.0, 15.0), egui::pos2(30.0, 15.0));
+        assert!(segment_overlaps_rect(&rect, &crossing.0, &crossing.1));
+        let passing = (egui::pos2(0.0, 12.0), egui::pos2(12.0, 0.0));
+        assert!(!segment_overlaps_rect(&rect, &passing.0, &passing.1));
+    }
+}
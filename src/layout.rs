@@ -0,0 +1,1025 @@
+use super::*;
+
+/// Options for Context::auto_layout
+#[derive(Debug)]
+pub struct LayoutOptions {
+    /// Horizontal space between two layers
+    pub layer_spacing: f32,
+    /// Vertical space between two nodes in the same layer
+    pub node_spacing: f32,
+    /// Number of up and down sweeps used to reduce link crossings
+    pub crossing_sweeps: usize,
+    /// Only move the selected nodes, links to the rest of the graph are ignored
+    pub selected_only: bool,
+}
+
+impl LayoutOptions {
+    pub const fn new() -> Self {
+        Self {
+            layer_spacing: 64.0,
+            node_spacing: 32.0,
+            crossing_sweeps: 8,
+            selected_only: false,
+        }
+    }
+}
+
+impl Default for LayoutOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Used by Context::align_selection
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Align {
+    Left,
+    Right,
+    Top,
+    Bottom,
+    /// Line up the horizontal centers of the nodes
+    CenterHorizontal,
+    /// Line up the vertical centers of the nodes
+    CenterVertical,
+}
+
+/// Used by Context::distribute_selection
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Distribute {
+    /// Equal horizontal space between the nodes
+    Horizontal,
+    /// Equal vertical space between the nodes
+    Vertical,
+}
+
+/// Options for a ForceLayout
+#[derive(Debug)]
+pub struct ForceLayoutOptions {
+    /// The preferred gap between two linked nodes
+    pub spring_length: f32,
+    /// How strongly links pull their nodes towards spring_length
+    pub spring_strength: f32,
+    /// How strongly nodes push each other away
+    pub repulsion: f32,
+    /// The largest distance a node can move in the first iteration
+    pub max_step: f32,
+    /// The factor the step size is multiplied by after every iteration
+    pub cooling: f32,
+    /// The layout has converged once no node moves further than this
+    pub min_step: f32,
+    /// Used by Context::force_layout to stop layouts that never settle
+    pub max_iterations: usize,
+    /// Only move the selected nodes, links to the rest of the graph are ignored
+    pub selected_only: bool,
+}
+
+impl ForceLayoutOptions {
+    pub const fn new() -> Self {
+        Self {
+            spring_length: 64.0,
+            spring_strength: 0.05,
+            repulsion: 4096.0,
+            max_step: 64.0,
+            cooling: 0.98,
+            min_step: 0.25,
+            max_iterations: 1000,
+            selected_only: false,
+        }
+    }
+}
+
+impl Default for ForceLayoutOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The state of a force directed layout, used with Context::force_layout and Context::force_layout_step.
+/// Links act as springs and nodes repel each other, which suits graphs without a natural flow direction
+#[derive(Debug)]
+pub struct ForceLayout<T = usize> {
+    pub options: ForceLayoutOptions,
+    pinned: HashSet<T>,
+    step: f32,
+    iterations: usize,
+    converged: bool,
+}
+
+impl<T: Hash + Eq> ForceLayout<T> {
+    pub fn new(options: ForceLayoutOptions) -> Self {
+        Self {
+            step: options.max_step,
+            options,
+            pinned: HashSet::new(),
+            iterations: 0,
+            converged: false,
+        }
+    }
+
+    /// Keep a node in place while the rest of the graph moves around it
+    pub fn pin_node(&mut self, node_id: T) {
+        self.pinned.insert(node_id);
+        self.converged = false;
+    }
+
+    pub fn unpin_node(&mut self, node_id: T) {
+        self.pinned.remove(&node_id);
+        self.converged = false;
+    }
+
+    pub fn is_node_pinned(&self, node_id: T) -> bool {
+        self.pinned.contains(&node_id)
+    }
+
+    /// Start settling again from the full step size, e.g. after the graph has changed
+    pub fn restart(&mut self) {
+        self.step = self.options.max_step;
+        self.iterations = 0;
+        self.converged = false;
+    }
+
+    pub fn converged(&self) -> bool {
+        self.converged
+    }
+
+    fn iterate(&mut self, rects: &mut [egui::Rect], edges: &[(usize, usize)], fixed: &[bool]) {
+        let mut forces = vec![egui::Vec2::ZERO; rects.len()];
+        for i in 0..rects.len() {
+            for j in (i + 1)..rects.len() {
+                let (direction, gap) = rect_separation(&rects[i], &rects[j]);
+                let force = direction * self.options.repulsion / gap.max(1.0).powi(2);
+                forces[i] -= force;
+                forces[j] += force;
+            }
+        }
+        for (from, to) in edges.iter() {
+            let (direction, gap) = rect_separation(&rects[*from], &rects[*to]);
+            let force =
+                direction * self.options.spring_strength * (gap - self.options.spring_length);
+            forces[*from] += force;
+            forces[*to] -= force;
+        }
+
+        let mut largest_move: f32 = 0.0;
+        for ((rect, force), fixed) in rects.iter_mut().zip(forces).zip(fixed) {
+            if *fixed {
+                continue;
+            }
+            let length = force.length();
+            if length > 0.0 {
+                let delta = force * (length.min(self.step) / length);
+                *rect = rect.translate(delta);
+                largest_move = largest_move.max(delta.length());
+            }
+        }
+
+        self.step *= self.options.cooling;
+        self.iterations += 1;
+        self.converged = largest_move < self.options.min_step
+            || self.step < self.options.min_step
+            || self.iterations >= self.options.max_iterations;
+    }
+}
+
+impl<T: Hash + Eq> Default for ForceLayout<T> {
+    fn default() -> Self {
+        Self::new(ForceLayoutOptions::new())
+    }
+}
+
+/// The unit direction from a to b and the gap between their edges, negative if they overlap
+fn rect_separation(a: &egui::Rect, b: &egui::Rect) -> (egui::Vec2, f32) {
+    let delta = b.center() - a.center();
+    let gap_x = delta.x.abs() - 0.5 * (a.width() + b.width());
+    let gap_y = delta.y.abs() - 0.5 * (a.height() + b.height());
+    let direction = if delta.length_sq() > 0.0 {
+        delta.normalized()
+    } else {
+        // stacked exactly on top of each other, any direction will do
+        egui::vec2(1.0, 0.0)
+    };
+    (direction, gap_x.max(gap_y))
+}
+
+impl<T: Hash + Eq + Clone> Context<T> {
+    /// Arranges the nodes in layers from left to right following the direction of the links.
+    /// Uses the node sizes from the last frame, so it should be called after the nodes have been shown once
+    pub fn auto_layout(&mut self, options: LayoutOptions) {
+        let (node_indices, edges) = self.layout_graph(options.selected_only);
+        if node_indices.is_empty() {
+            return;
+        }
+
+        let sizes: Vec<egui::Vec2> =
+            node_indices.iter().map(|x| self.nodes.pool[*x].layout_size()).collect();
+        let positions = layered_layout(&sizes, &edges, &options);
+
+        let anchor = node_indices
+            .iter()
+            .map(|x| self.nodes.pool[*x].origin)
+            .fold(egui::pos2(f32::MAX, f32::MAX), |acc, x| acc.min(x));
+        for (idx, pos) in node_indices.into_iter().zip(positions) {
+            self.set_node_origin(idx, anchor + pos.to_vec2());
+        }
+    }
+
+    /// Aligns the selected nodes with the outermost edge or the center of the selection.
+    /// If snap_to_grid is set the aligned edge or center is moved onto the nearest grid line
+    pub fn align_selection(&mut self, align: Align, snap_to_grid: bool) {
+        let rects: Vec<(usize, egui::Rect)> = self
+            .selected_node_indices
+            .iter()
+            .map(|x| (*x, self.nodes.pool[*x].layout_rect()))
+            .collect();
+        if rects.is_empty() {
+            return;
+        }
+        let bounds = rects.iter().fold(rects[0].1, |acc, (_, rect)| acc.union(*rect));
+
+        let (target, edge): (f32, fn(&egui::Rect) -> egui::Pos2) = match align {
+            Align::Left => (bounds.min.x, |rect| rect.left_top()),
+            Align::Right => (bounds.max.x, |rect| rect.right_top()),
+            Align::Top => (bounds.min.y, |rect| rect.left_top()),
+            Align::Bottom => (bounds.max.y, |rect| rect.left_bottom()),
+            Align::CenterHorizontal => (bounds.center().x, |rect| rect.center()),
+            Align::CenterVertical => (bounds.center().y, |rect| rect.center()),
+        };
+        let target = if snap_to_grid {
+            self.snap_to_grid(target)
+        } else {
+            target
+        };
+        let horizontal = matches!(align, Align::Left | Align::Right | Align::CenterHorizontal);
+
+        for (idx, rect) in rects {
+            let edge = edge(&rect);
+            let delta = if horizontal {
+                egui::vec2(target - edge.x, 0.0)
+            } else {
+                egui::vec2(0.0, target - edge.y)
+            };
+            self.move_node_by(idx, delta);
+        }
+    }
+
+    /// Spaces the selected nodes evenly between the two outermost nodes of the selection.
+    /// If snap_to_grid is set each node is moved onto the nearest grid line
+    pub fn distribute_selection(&mut self, distribute: Distribute, snap_to_grid: bool) {
+        let axis = |v: egui::Vec2| match distribute {
+            Distribute::Horizontal => v.x,
+            Distribute::Vertical => v.y,
+        };
+        let mut rects: Vec<(usize, egui::Rect)> = self
+            .selected_node_indices
+            .iter()
+            .map(|x| (*x, self.nodes.pool[*x].layout_rect()))
+            .collect();
+        if rects.len() < 3 {
+            return;
+        }
+        rects.sort_by(|a, b| {
+            axis(a.1.min.to_vec2())
+                .partial_cmp(&axis(b.1.min.to_vec2()))
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+
+        let start = axis(rects[0].1.min.to_vec2());
+        let end = axis(rects[rects.len() - 1].1.max.to_vec2());
+        let total_size: f32 = rects.iter().map(|(_, rect)| axis(rect.size())).sum();
+        let gap = (end - start - total_size) / (rects.len() - 1) as f32;
+
+        let mut next = start;
+        for (idx, rect) in rects {
+            let target = if snap_to_grid {
+                self.snap_to_grid(next)
+            } else {
+                next
+            };
+            let offset = target - axis(rect.min.to_vec2());
+            let delta = match distribute {
+                Distribute::Horizontal => egui::vec2(offset, 0.0),
+                Distribute::Vertical => egui::vec2(0.0, offset),
+            };
+            self.move_node_by(idx, delta);
+            next += axis(rect.size()) + gap;
+        }
+    }
+
+    /// Pushes overlapping nodes apart until they are at least Style::node_separation apart.
+    /// Useful after inserting nodes programmatically
+    pub fn resolve_overlaps(&mut self) {
+        self.separate_nodes(&[]);
+    }
+
+    /// Runs a force directed layout until it settles or options.max_iterations is reached
+    pub fn force_layout(&mut self, layout: &mut ForceLayout<T>) {
+        let remaining = layout.options.max_iterations.saturating_sub(layout.iterations);
+        self.force_layout_step(layout, remaining);
+    }
+
+    /// Advances a force directed layout by a number of iterations so it can be watched as it settles.
+    /// Returns true once the layout has converged
+    pub fn force_layout_step(&mut self, layout: &mut ForceLayout<T>, iterations: usize) -> bool {
+        let (node_indices, edges) = self.layout_graph(layout.options.selected_only);
+        let rects: Vec<egui::Rect> =
+            node_indices.iter().map(|x| self.nodes.pool[*x].layout_rect()).collect();
+        // nodes that are being dragged stay where the user puts them
+        let dragging = self.click_interaction_type == ClickInteractionType::Node;
+        let fixed: Vec<bool> = node_indices
+            .iter()
+            .map(|x| {
+                layout.pinned.contains(&self.nodes.pool[*x].id)
+                    || (dragging && self.selected_node_indices.contains(x))
+            })
+            .collect();
+
+        let mut rects = rects;
+        for _ in 0..iterations {
+            if layout.converged() {
+                break;
+            }
+            layout.iterate(&mut rects, &edges, &fixed);
+        }
+
+        for (idx, rect) in node_indices.into_iter().zip(rects) {
+            let origin = rect.min + self.nodes.pool[idx].layout_style.padding;
+            if self.nodes.pool[idx].origin != origin {
+                self.set_node_origin(idx, origin);
+            }
+        }
+        layout.converged()
+    }
+
+    /// Moves nodes the shortest distance that separates them. Fixed and non draggable nodes are not moved
+    pub(crate) fn separate_nodes(&mut self, fixed: &[usize]) {
+        const MAX_PASSES: usize = 32;
+        let half_separation = 0.5 * self.style.node_separation;
+        let node_indices: Vec<usize> =
+            (0..self.nodes.pool.len()).filter(|x| self.nodes.in_use[*x]).collect();
+        let movable: Vec<bool> = node_indices
+            .iter()
+            .map(|x| self.nodes.pool[*x].draggable && !fixed.contains(x))
+            .collect();
+        let mut rects: Vec<egui::Rect> = node_indices
+            .iter()
+            .map(|x| self.nodes.pool[*x].layout_rect().expand(half_separation))
+            .collect();
+
+        for _ in 0..MAX_PASSES {
+            let mut moved = false;
+            for i in 0..rects.len() {
+                for j in (i + 1)..rects.len() {
+                    if !(movable[i] || movable[j]) || !rects[i].intersects(rects[j]) {
+                        continue;
+                    }
+                    let overlap = rects[i].intersect(rects[j]);
+                    let delta = rects[j].center() - rects[i].center();
+                    let push = if overlap.width() < overlap.height() {
+                        egui::vec2(
+                            if delta.x < 0.0 {
+                                -overlap.width()
+                            } else {
+                                overlap.width()
+                            },
+                            0.0,
+                        )
+                    } else {
+                        egui::vec2(
+                            0.0,
+                            if delta.y < 0.0 {
+                                -overlap.height()
+                            } else {
+                                overlap.height()
+                            },
+                        )
+                    };
+                    if push == egui::Vec2::ZERO {
+                        continue;
+                    }
+                    let share_i = match (movable[i], movable[j]) {
+                        (true, true) => 0.5,
+                        (true, false) => 1.0,
+                        _ => 0.0,
+                    };
+                    rects[i] = rects[i].translate(-push * share_i);
+                    rects[j] = rects[j].translate(push * (1.0 - share_i));
+                    moved = true;
+                }
+            }
+            if !moved {
+                break;
+            }
+        }
+
+        for (idx, rect) in node_indices.into_iter().zip(rects) {
+            let origin =
+                rect.shrink(half_separation).min + self.nodes.pool[idx].layout_style.padding;
+            if origin != self.nodes.pool[idx].origin {
+                self.set_node_origin(idx, origin);
+            }
+        }
+    }
+
+    fn move_node_by(&mut self, node_idx: usize, delta: egui::Vec2) {
+        if self.nodes.pool[node_idx].draggable {
+            self.set_node_origin(node_idx, self.nodes.pool[node_idx].origin + delta);
+        }
+    }
+
+    fn snap_to_grid(&self, v: f32) -> f32 {
+        (v / self.style.grid_spacing).round() * self.style.grid_spacing
+    }
+
+    /// The nodes to be laid out and the links between them as directed pairs of indices into the node list
+    fn layout_graph(&self, selected_only: bool) -> (Vec<usize>, Vec<(usize, usize)>) {
+        let node_indices: Vec<usize> = if selected_only {
+            self.selected_node_indices.clone()
+        } else {
+            (0..self.nodes.pool.len()).filter(|x| self.nodes.in_use[*x]).collect()
+        };
+
+        let local: HashMap<usize, usize> =
+            node_indices.iter().enumerate().map(|(i, idx)| (*idx, i)).collect();
+        let mut edges = Vec::new();
+        for (idx, link) in self.links.pool.iter().enumerate() {
+            if !self.links.in_use[idx] {
+                continue;
+            }
+            let (from, to) = self.link_node_direction(link.start_pin_index, link.end_pin_index);
+            if let (Some(from), Some(to)) = (local.get(&from), local.get(&to)) {
+                if from != to && !edges.contains(&(*from, *to)) {
+                    edges.push((*from, *to));
+                }
+            }
+        }
+        (node_indices, edges)
+    }
+}
+
+/// Computes the top left corner of each node for a layered left to right layout.
+/// edges are directed pairs of indices into sizes
+pub(crate) fn layered_layout(
+    sizes: &[egui::Vec2],
+    edges: &[(usize, usize)],
+    options: &LayoutOptions,
+) -> Vec<egui::Pos2> {
+    let edges = remove_cycles(sizes.len(), edges);
+    let layer_of = assign_layers(sizes.len(), &edges);
+
+    // split links that span several layers with virtual nodes
+    let mut layer_of_vertex = layer_of.clone();
+    let mut chain_edges = Vec::new();
+    for (from, to) in edges {
+        let mut prev = from;
+        for layer in (layer_of[from] + 1)..layer_of[to] {
+            layer_of_vertex.push(layer);
+            chain_edges.push((prev, layer_of_vertex.len() - 1));
+            prev = layer_of_vertex.len() - 1;
+        }
+        chain_edges.push((prev, to));
+    }
+
+    let num_layers = layer_of.iter().max().map_or(0, |x| x + 1);
+    let mut layers = vec![Vec::new(); num_layers];
+    for (vertex, layer) in layer_of_vertex.iter().enumerate() {
+        layers[*layer].push(vertex);
+    }
+    let mut predecessors = vec![Vec::new(); layer_of_vertex.len()];
+    let mut successors = vec![Vec::new(); layer_of_vertex.len()];
+    for (from, to) in chain_edges.iter() {
+        successors[*from].push(*to);
+        predecessors[*to].push(*from);
+    }
+
+    minimise_crossings(
+        &mut layers,
+        &predecessors,
+        &successors,
+        options.crossing_sweeps,
+    );
+
+    let height = |vertex: usize| sizes.get(vertex).map_or(0.0, |x| x.y);
+    let mut y = vec![0.0; layer_of_vertex.len()];
+    for layer in layers.iter() {
+        let mut next = 0.0;
+        for vertex in layer.iter() {
+            y[*vertex] = next;
+            next += height(*vertex) + options.node_spacing;
+        }
+    }
+    // pull each layer towards its neighbours, alternating directions so both sides have a say
+    for sweep in 0..options.crossing_sweeps.max(1) * 2 {
+        let (order, neighbours): (Vec<usize>, _) = if sweep % 2 == 0 {
+            ((1..num_layers).collect(), &predecessors)
+        } else {
+            (
+                (0..num_layers.saturating_sub(1)).rev().collect(),
+                &successors,
+            )
+        };
+        for layer_idx in order {
+            let layer = &layers[layer_idx];
+            let desired: Vec<f32> = layer
+                .iter()
+                .map(|vertex| {
+                    let centers: Vec<f32> =
+                        neighbours[*vertex].iter().map(|x| y[*x] + 0.5 * height(*x)).collect();
+                    if centers.is_empty() {
+                        y[*vertex]
+                    } else {
+                        centers.iter().sum::<f32>() / centers.len() as f32 - 0.5 * height(*vertex)
+                    }
+                })
+                .collect();
+            let heights: Vec<f32> = layer.iter().map(|x| height(*x)).collect();
+            let placed = place_in_order(&desired, &heights, options.node_spacing);
+            for (vertex, pos) in layer.iter().zip(placed) {
+                y[*vertex] = pos;
+            }
+        }
+    }
+
+    let min_y = y.iter().take(sizes.len()).copied().fold(f32::MAX, f32::min);
+    let mut positions = vec![egui::Pos2::ZERO; sizes.len()];
+    let mut x = 0.0;
+    for layer in layers.iter() {
+        let mut width: f32 = 0.0;
+        for vertex in layer.iter().filter(|x| **x < sizes.len()) {
+            positions[*vertex] = egui::pos2(x, y[*vertex] - min_y);
+            width = width.max(sizes[*vertex].x);
+        }
+        x += width + options.layer_spacing;
+    }
+    positions
+}
+
+/// Reverses the links that close a cycle so the graph can be split into layers
+fn remove_cycles(num_vertices: usize, edges: &[(usize, usize)]) -> Vec<(usize, usize)> {
+    let mut successors = vec![Vec::new(); num_vertices];
+    for (from, to) in edges {
+        successors[*from].push(*to);
+    }
+
+    // 0: unvisited, 1: on the current path, 2: finished
+    let mut state = vec![0u8; num_vertices];
+    let mut back_edges = HashSet::new();
+    for root in 0..num_vertices {
+        if state[root] != 0 {
+            continue;
+        }
+        state[root] = 1;
+        let mut stack = vec![(root, 0)];
+        while let Some((vertex, next)) = stack.pop() {
+            if let Some(successor) = successors[vertex].get(next).copied() {
+                stack.push((vertex, next + 1));
+                match state[successor] {
+                    0 => {
+                        state[successor] = 1;
+                        stack.push((successor, 0));
+                    }
+                    1 => {
+                        back_edges.insert((vertex, successor));
+                    }
+                    _ => (),
+                }
+            } else {
+                state[vertex] = 2;
+            }
+        }
+    }
+
+    edges
+        .iter()
+        .map(|(from, to)| {
+            if back_edges.contains(&(*from, *to)) {
+                (*to, *from)
+            } else {
+                (*from, *to)
+            }
+        })
+        .collect()
+}
+
+/// Longest path layering, every vertex is placed one layer after its furthest predecessor
+fn assign_layers(num_vertices: usize, edges: &[(usize, usize)]) -> Vec<usize> {
+    let mut in_degree = vec![0; num_vertices];
+    let mut successors = vec![Vec::new(); num_vertices];
+    for (from, to) in edges {
+        in_degree[*to] += 1;
+        successors[*from].push(*to);
+    }
+
+    let mut layer = vec![0; num_vertices];
+    let mut queue: Vec<usize> = (0..num_vertices).filter(|x| in_degree[*x] == 0).collect();
+    while let Some(vertex) = queue.pop() {
+        for successor in successors[vertex].iter() {
+            layer[*successor] = layer[*successor].max(layer[vertex] + 1);
+            in_degree[*successor] -= 1;
+            if in_degree[*successor] == 0 {
+                queue.push(*successor);
+            }
+        }
+    }
+
+    // sources only feed later layers, move them next to their closest successor
+    let has_predecessor: HashSet<usize> = edges.iter().map(|(_, to)| *to).collect();
+    for vertex in 0..num_vertices {
+        if !has_predecessor.contains(&vertex) {
+            if let Some(closest) = successors[vertex].iter().map(|x| layer[*x]).min() {
+                layer[vertex] = closest - 1;
+            }
+        }
+    }
+    layer
+}
+
+/// Barycenter heuristic, keeps the ordering with the fewest crossings seen
+fn minimise_crossings(
+    layers: &mut [Vec<usize>],
+    predecessors: &[Vec<usize>],
+    successors: &[Vec<usize>],
+    sweeps: usize,
+) {
+    let mut position = vec![0.0; predecessors.len()];
+    let update_positions = |layers: &[Vec<usize>], position: &mut Vec<f32>| {
+        for layer in layers.iter() {
+            for (i, vertex) in layer.iter().enumerate() {
+                position[*vertex] = i as f32;
+            }
+        }
+    };
+    update_positions(layers, &mut position);
+
+    let mut best = layers.to_vec();
+    let mut best_crossings = count_crossings(layers, successors, &position);
+    for sweep in 0..sweeps {
+        let (order, neighbours): (Vec<usize>, _) = if sweep % 2 == 0 {
+            ((1..layers.len()).collect(), predecessors)
+        } else {
+            (
+                (0..layers.len().saturating_sub(1)).rev().collect(),
+                successors,
+            )
+        };
+        for layer_idx in order {
+            let mut keyed: Vec<(f32, usize)> = layers[layer_idx]
+                .iter()
+                .map(|vertex| {
+                    let adjacent = &neighbours[*vertex];
+                    let key = if adjacent.is_empty() {
+                        position[*vertex]
+                    } else {
+                        adjacent.iter().map(|x| position[*x]).sum::<f32>() / adjacent.len() as f32
+                    };
+                    (key, *vertex)
+                })
+                .collect();
+            keyed.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+            layers[layer_idx] = keyed.into_iter().map(|(_, x)| x).collect();
+            for (i, vertex) in layers[layer_idx].iter().enumerate() {
+                position[*vertex] = i as f32;
+            }
+        }
+
+        let crossings = count_crossings(layers, successors, &position);
+        if crossings < best_crossings {
+            best_crossings = crossings;
+            best = layers.to_vec();
+        }
+    }
+    layers.clone_from_slice(&best);
+}
+
+fn count_crossings(layers: &[Vec<usize>], successors: &[Vec<usize>], position: &[f32]) -> usize {
+    let mut crossings = 0;
+    for layer in layers.iter() {
+        let edges: Vec<(f32, f32)> = layer
+            .iter()
+            .flat_map(|from| successors[*from].iter().map(move |to| (*from, *to)))
+            .map(|(from, to)| (position[from], position[to]))
+            .collect();
+        for (i, a) in edges.iter().enumerate() {
+            for b in edges[(i + 1)..].iter() {
+                if (a.0 - b.0) * (a.1 - b.1) < 0.0 {
+                    crossings += 1;
+                }
+            }
+        }
+    }
+    crossings
+}
+
+/// Places items as close to their desired positions as possible without changing their order
+fn place_in_order(desired: &[f32], heights: &[f32], spacing: f32) -> Vec<f32> {
+    let mut placed: Vec<f32> = Vec::with_capacity(desired.len());
+    for (i, want) in desired.iter().enumerate() {
+        let min = placed.last().map_or(f32::MIN, |prev| prev + heights[i - 1] + spacing);
+        placed.push(want.max(min));
+    }
+    // the forward pass only pushes down, shift back up so the layer is centered on its targets
+    if !placed.is_empty() {
+        let shift =
+            placed.iter().zip(desired).map(|(p, d)| p - d).sum::<f32>() / placed.len() as f32;
+        placed.iter_mut().for_each(|x| *x -= shift);
+    }
+    placed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Harness;
+
+    fn overlapping(rects: &[egui::Rect]) -> bool {
+        rects.iter().enumerate().any(|(i, a)| rects[(i + 1)..].iter().any(|b| a.intersects(*b)))
+    }
+
+    #[test]
+    fn layered_layout_places_links_left_to_right_without_overlaps() {
+        // a diamond with a link that skips the middle layer
+        let sizes = vec![
+            egui::vec2(80.0, 40.0),
+            egui::vec2(100.0, 60.0),
+            egui::vec2(60.0, 30.0),
+        ]
+        .into_iter()
+        .cycle()
+        .take(4)
+        .collect::<Vec<_>>();
+        let edges = [(0, 1), (0, 2), (1, 3), (2, 3), (0, 3)];
+        let options = LayoutOptions::new();
+        let positions = layered_layout(&sizes, &edges, &options);
+
+        assert_eq!(assign_layers(4, &edges), vec![0, 1, 1, 2]);
+        assert_eq!(positions[1].x, positions[2].x);
+        assert_eq!(positions[1].x, sizes[0].x + options.layer_spacing);
+        assert_eq!(
+            positions[3].x,
+            positions[1].x + 100.0 + options.layer_spacing
+        );
+        let rects = positions
+            .iter()
+            .zip(sizes.iter())
+            .map(|(pos, size)| egui::Rect::from_min_size(*pos, *size))
+            .collect::<Vec<_>>();
+        assert!(!overlapping(&rects));
+        assert_eq!(rects.iter().map(|x| x.min.y).fold(f32::MAX, f32::min), 0.0);
+    }
+
+    #[test]
+    fn cycles_are_broken_before_layering() {
+        let edges = remove_cycles(3, &[(0, 1), (1, 2), (2, 0)]);
+        assert_eq!(edges, vec![(0, 1), (1, 2), (0, 2)]);
+        assert_eq!(assign_layers(3, &edges), vec![0, 1, 2]);
+
+        let sizes = vec![egui::vec2(50.0, 50.0); 3];
+        let positions = layered_layout(&sizes, &[(0, 1), (1, 2), (2, 0)], &LayoutOptions::new());
+        assert!(positions[0].x < positions[1].x && positions[1].x < positions[2].x);
+    }
+
+    /// Runs a ForceLayout over a triangle with a tail until it converges
+    fn settle_force_layout(layout: &mut ForceLayout) -> Vec<egui::Rect> {
+        let mut rects = (0..4)
+            .map(|i| {
+                egui::Rect::from_min_size(egui::pos2(10.0 * i as f32, 0.0), egui::vec2(40.0, 20.0))
+            })
+            .collect::<Vec<_>>();
+        let edges = [(0, 1), (1, 2), (2, 0), (2, 3)];
+        while !layout.converged() {
+            layout.iterate(&mut rects, &edges, &[false; 4]);
+        }
+        rects
+    }
+
+    #[test]
+    fn force_layout_converges_deterministically() {
+        let mut layout = ForceLayout::default();
+        let rects = settle_force_layout(&mut layout);
+        assert!(layout.iterations < layout.options.max_iterations);
+        assert!(!overlapping(&rects));
+        // linked nodes end up close to each other
+        for (from, to) in [(0, 1), (1, 2), (2, 3)] {
+            let (_, gap) = rect_separation(&rects[from], &rects[to]);
+            assert!(gap < 2.0 * layout.options.spring_length);
+        }
+
+        let mut again = ForceLayout::default();
+        assert_eq!(settle_force_layout(&mut again), rects);
+        assert_eq!(again.iterations, layout.iterations);
+
+        layout.restart();
+        assert!(!layout.converged());
+    }
+
+    /// Shows count nodes stacked on top of each other, node n is linked to node n + 1
+    fn stacked(count: usize) -> impl FnMut(&mut Context, &mut egui::Ui) {
+        move |ctx, ui| {
+            let nodes = (0..count)
+                .map(|n| {
+                    NodeConstructor::new(n, Default::default())
+                        .with_origin([100.0, 100.0].into())
+                        .with_title(move |ui| ui.label("Node".repeat(n + 1)))
+                        .with_input_attribute(2 * n, Default::default(), |ui| ui.label("In"))
+                        .with_output_attribute(2 * n + 1, Default::default(), |ui| ui.label("Out"))
+                })
+                .collect::<Vec<_>>();
+            let links = (1..count).map(|n| (n, 2 * n - 1, 2 * n, LinkArgs::default()));
+            ctx.show(nodes, links, ui);
+        }
+    }
+
+    fn node_rects(harness: &Harness, count: usize) -> Vec<egui::Rect> {
+        (0..count).map(|n| harness.node_rect(n).unwrap()).collect()
+    }
+
+    #[test]
+    fn auto_layout_separates_stacked_nodes() {
+        let mut harness = Harness::new();
+        let mut show = stacked(3);
+        harness.settle(&mut show);
+        assert!(overlapping(&node_rects(&harness, 3)));
+
+        harness.ctx.auto_layout(LayoutOptions::new());
+        harness.run(&mut show);
+        let rects = node_rects(&harness, 3);
+        assert!(!overlapping(&rects));
+        assert!(rects[0].max.x < rects[1].min.x && rects[1].max.x < rects[2].min.x);
+        // the layout starts where the nodes were
+        assert_eq!(
+            harness.ctx.get_node_pos_grid_space(0),
+            Some([100.0, 100.0].into())
+        );
+    }
+
+    #[test]
+    fn force_layout_keeps_pinned_nodes_in_place() {
+        let mut harness = Harness::new();
+        let mut show = stacked(3);
+        harness.settle(&mut show);
+
+        let mut layout = ForceLayout::default();
+        layout.pin_node(1);
+        assert!(!harness.ctx.force_layout_step(&mut layout, 1));
+        harness.ctx.force_layout(&mut layout);
+        assert!(layout.converged());
+        harness.run(&mut show);
+        assert_eq!(
+            harness.ctx.get_node_pos_grid_space(1),
+            Some([100.0, 100.0].into())
+        );
+        assert!(!overlapping(&node_rects(&harness, 3)));
+    }
+
+    #[test]
+    fn force_layout_separates_nodes_with_different_padding() {
+        let mut harness = Harness::new();
+        let mut show = |ctx: &mut Context, ui: &mut egui::Ui| {
+            let nodes = (0..3)
+                .map(|n| {
+                    let args = NodeArgs {
+                        padding: Some(egui::vec2(8.0, 8.0) + egui::vec2(16.0, 16.0) * n as f32),
+                        ..Default::default()
+                    };
+                    NodeConstructor::new(n, args)
+                        .with_origin([100.0, 100.0].into())
+                        .with_title(|ui| ui.label("Node"))
+                })
+                .collect::<Vec<_>>();
+            ctx.show(nodes, Vec::new(), ui);
+        };
+        harness.settle(&mut show);
+
+        let mut layout = ForceLayout::default();
+        harness.ctx.force_layout(&mut layout);
+        assert!(layout.converged());
+        harness.run(&mut show);
+        assert!(!overlapping(&node_rects(&harness, 3)));
+    }
+
+    /// Shows unlinked nodes of different sizes at the origins and selects them all
+    fn scattered(
+        origins: &'static [[f32; 2]],
+    ) -> (Harness, impl FnMut(&mut Context, &mut egui::Ui)) {
+        let mut show = move |ctx: &mut Context, ui: &mut egui::Ui| {
+            let nodes = origins
+                .iter()
+                .enumerate()
+                .map(|(n, origin)| {
+                    NodeConstructor::new(n, Default::default())
+                        .with_origin((*origin).into())
+                        .with_title(move |ui| ui.label("Node".repeat(n + 1)))
+                        .with_output_attribute(n, Default::default(), move |ui| {
+                            ui.label("Out\n".repeat(origins.len() - n))
+                        })
+                })
+                .collect::<Vec<_>>();
+            ctx.show(nodes, Vec::new(), ui);
+        };
+        let mut harness = Harness::new();
+        harness.settle(&mut show);
+        harness.ctx.selected_node_indices =
+            (0..origins.len()).map(|n| harness.ctx.nodes.find(&n).unwrap()).collect();
+        (harness, show)
+    }
+
+    const ORIGINS: &[[f32; 2]] = &[[40.0, 300.0], [330.0, 60.0], [200.0, 180.0]];
+
+    #[test]
+    fn align_selection_lines_up_edges_and_centers() {
+        let (mut harness, mut show) = scattered(ORIGINS);
+        let rects = node_rects(&harness, 3);
+        harness.ctx.align_selection(Align::Left, false);
+        harness.run(&mut show);
+        let aligned = node_rects(&harness, 3);
+        assert!(aligned.iter().all(|x| x.min.x == rects[0].min.x));
+        assert!(aligned.iter().zip(rects.iter()).all(|(a, b)| a.min.y == b.min.y));
+
+        harness.ctx.align_selection(Align::Bottom, false);
+        harness.run(&mut show);
+        let bottom = rects.iter().map(|x| x.max.y).fold(f32::MIN, f32::max);
+        assert!(node_rects(&harness, 3).iter().all(|x| x.max.y == bottom));
+
+        harness.ctx.align_selection(Align::CenterVertical, true);
+        harness.run(&mut show);
+        let rects = node_rects(&harness, 3);
+        let center = harness.ctx.screen_space_to_grid_space(rects[0].center()).y;
+        assert!(rects.iter().all(|x| x.center().y == rects[0].center().y));
+        assert_eq!(center % harness.ctx.style.grid_spacing, 0.0);
+    }
+
+    #[test]
+    fn distribute_selection_spaces_nodes_evenly() {
+        let (mut harness, mut show) = scattered(ORIGINS);
+        let rects = node_rects(&harness, 3);
+        harness.ctx.distribute_selection(Distribute::Horizontal, false);
+        harness.run(&mut show);
+        let distributed = node_rects(&harness, 3);
+        // the outermost nodes stay, sorted by their left edge the gaps are equal
+        assert_eq!(distributed[0], rects[0]);
+        assert_eq!(distributed[1].max.x, rects[1].max.x);
+        let gaps = (
+            distributed[2].min.x - distributed[0].max.x,
+            distributed[1].min.x - distributed[2].max.x,
+        );
+        assert!((gaps.0 - gaps.1).abs() < 1e-3);
+
+        harness.ctx.distribute_selection(Distribute::Vertical, true);
+        harness.run(&mut show);
+        let distributed = node_rects(&harness, 3);
+        let grid_spacing = harness.ctx.style.grid_spacing;
+        for rect in distributed.iter() {
+            let top = harness.ctx.screen_space_to_grid_space(rect.min).y;
+            assert_eq!(top % grid_spacing, 0.0);
+        }
+        assert!(
+            distributed[1].max.y < distributed[2].min.y
+                && distributed[2].max.y < distributed[0].min.y
+        );
+    }
+
+    #[test]
+    fn small_selections_are_aligned_but_not_distributed() {
+        for count in 0..=2 {
+            let (mut harness, mut show) = scattered(&ORIGINS[..count]);
+            let rects = node_rects(&harness, count);
+            // distributing needs a node between the outermost two
+            harness.ctx.distribute_selection(Distribute::Horizontal, false);
+            harness.ctx.distribute_selection(Distribute::Vertical, false);
+            harness.run(&mut show);
+            assert_eq!(node_rects(&harness, count), rects);
+
+            harness.ctx.align_selection(Align::Right, false);
+            harness.ctx.align_selection(Align::Top, false);
+            harness.run(&mut show);
+            let aligned = node_rects(&harness, count);
+            let right = rects.iter().map(|x| x.max.x).fold(f32::MIN, f32::max);
+            let top = rects.iter().map(|x| x.min.y).fold(f32::MAX, f32::min);
+            assert!(aligned.iter().all(|x| x.max.x == right && x.min.y == top));
+            if count == 1 {
+                // a single node is already aligned with itself
+                assert_eq!(aligned, rects);
+            }
+        }
+    }
+
+    #[test]
+    fn resolve_overlaps_keeps_nodes_apart_by_the_separation() {
+        let mut harness = Harness::new();
+        let mut show = stacked(3);
+        harness.settle(&mut show);
+        harness.ctx.set_node_draggable(2, false);
+        harness.ctx.resolve_overlaps();
+        harness.run(&mut show);
+
+        let separation = harness.ctx.style.node_separation;
+        let rects = node_rects(&harness, 3)
+            .into_iter()
+            .map(|x| x.expand(0.5 * separation - 1e-3))
+            .collect::<Vec<_>>();
+        assert!(!overlapping(&rects));
+        assert_eq!(
+            harness.ctx.get_node_pos_grid_space(2),
+            Some([100.0, 100.0].into())
+        );
+    }
+}
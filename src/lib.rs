@@ -42,34 +42,78 @@
 //!     }
 //! }
 //! ```
+//!
+//! Node, pin and link ids can be of any type that is `Hash + Eq + Clone`, by default they are `usize`.
+//! Nodes, pins and links each have their own namespace. With `egui::Id` pins can be scoped to their node,
+//! so ids don't need to be packed by hand:
+//! ``` rust
+//! use egui_nodes::{Context, NodeConstructor, LinkArgs};
+//! use egui::{Id, Ui};
+//!
+//! pub fn scoped_graph(ctx: &mut Context<Id>, links: &[(Id, Id)], ui: &mut Ui) {
+//!     let (source, sink) = (Id::new("source"), Id::new("sink"));
+//!     let nodes = vec![
+//!         NodeConstructor::new(source, Default::default())
+//!             .with_output_attribute(source.with(0), Default::default(), |ui| ui.label("Output")),
+//!         NodeConstructor::new(sink, Default::default())
+//!             .with_input_attribute(sink.with(0), Default::default(), |ui| ui.label("Input")),
+//!     ];
+//!
+//!     // a link is identified by the pins it connects
+//!     ctx.show(
+//!         nodes,
+//!         links.iter().map(|(start, end)| (start.with(end), *start, *end, LinkArgs::default())),
+//!         ui
+//!     );
+//! }
+//! ```
 
 use derivative::Derivative;
-use std::collections::HashMap;
+use std::collections::{hash_map::Entry, HashMap, HashSet};
+use std::hash::Hash;
 
+mod drag_drop;
+mod editor;
+pub mod geometry;
+mod layout;
 mod link;
 mod node;
+mod palette;
 mod pin;
+mod spatial;
 mod style;
+#[cfg(any(test, feature = "test-support"))]
+pub mod testing;
 
 use link::*;
 use node::*;
 use pin::*;
+use spatial::*;
 
 pub use {
+    drag_drop::drag_source,
+    editor::{NodeEditor, NodeEditorEvent, NodeEditorResponse},
+    layout::{Align, Distribute, ForceLayout, ForceLayoutOptions, LayoutOptions},
     link::LinkArgs,
-    node::{NodeArgs, NodeConstructor},
-    pin::{AttributeFlags, PinArgs, PinShape},
+    node::{NodeArgs, NodeConstructor, SlotId},
+    palette::{NodePalette, PaletteEntry, PaletteSelection, PortRef},
+    pin::{AttributeFlags, AttributeType, PinArgs, PinKind, PinShape, PinShapeState, PinSide},
     style::{ColorStyle, Style, StyleFlags, StyleVar},
 };
 
 /// The Context that tracks the state of the node editor
 #[derive(Derivative)]
-#[derivative(Default, Debug)]
-pub struct Context {
+#[derivative(Default(bound = ""), Debug)]
+pub struct Context<T = usize> {
     node_idx_submission_order: Vec<usize>,
     node_indices_overlapping_with_mouse: Vec<usize>,
-    occluded_pin_indices: Vec<usize>,
+    occluded_pin_indices: HashSet<usize>,
 
+    node_grid: SpatialGrid,
+    pin_grid: SpatialGrid,
+    link_grid: SpatialGrid,
+
+    id: Option<egui::Id>,
     canvas_origin_screen_space: egui::Vec2,
     #[derivative(Default(value = "[[0.0; 2].into(); 2].into()"))]
     canvas_rect_screen_space: egui::Rect,
@@ -97,8 +141,7 @@ pub struct Context {
 
     element_state_change: usize,
 
-    active_attribute_id: usize,
-    active_attribute: bool,
+    active_attribute_id: Option<T>,
 
     mouse_pos: egui::Pos2,
     mouse_delta: egui::Vec2,
@@ -110,10 +153,27 @@ pub struct Context {
     alt_mouse_dragging: bool,
     mouse_in_canvas: bool,
     link_detatch_with_modifier_click: bool,
+    minimap_hovered: bool,
+    secondary_mouse_clicked: bool,
+    context_menu_target: Option<ContextMenuTarget<T>>,
+    #[derivative(Debug = "ignore")]
+    dropped_payload: Option<(drag_drop::DragPayload, egui::Pos2)>,
 
-    nodes: ObjectPool<NodeData>,
-    pins: ObjectPool<PinData>,
-    links: ObjectPool<LinkData>,
+    interaction_mode: InteractionMode,
+    minimap: bool,
+
+    acyclic: bool,
+    resolve_overlaps_on_drop: bool,
+    #[derivative(Default(value = "true"))]
+    viewport_culling: bool,
+    culling_stats: CullingStats,
+
+    diagnostics: Vec<Diagnostic<T>>,
+    diagnostics_overlay: bool,
+
+    nodes: ObjectPool<NodeData<T>, T>,
+    pins: ObjectPool<PinData<T>, T>,
+    links: ObjectPool<LinkData<T>, T>,
     nodes_map: HashMap<usize, usize>,
     nodes_free: Vec<usize>,
 
@@ -129,12 +189,26 @@ pub struct Context {
     click_interaction_state: ClickInteractionState,
 }
 
-impl Context {
+impl<T: Hash + Eq + Clone> Context<T> {
+    /// Create a Context that salts the ids of all of its ui with id.
+    /// Needed to show more than one editor in the same egui Ui, each with its own id
+    pub fn with_id(id: egui::Id) -> Self {
+        Self {
+            id: Some(id),
+            ..Default::default()
+        }
+    }
+
+    /// Change the id used to salt the ui of the editor, see Context::with_id
+    pub fn set_id(&mut self, id: egui::Id) {
+        self.id.replace(id);
+    }
+
     /// Displays the current state of the editor on a give Egui Ui as well as updating user input to the context
     pub fn show<'a>(
         &mut self,
-        nodes: impl IntoIterator<Item = NodeConstructor<'a>>,
-        links: impl IntoIterator<Item = (usize, usize, usize, LinkArgs)>,
+        nodes: impl IntoIterator<Item = NodeConstructor<'a, T>>,
+        links: impl IntoIterator<Item = (T, T, T, LinkArgs)>,
         ui: &mut egui::Ui,
     ) -> egui::Response {
         let rect = ui.available_rect_before_wrap();
@@ -153,17 +227,22 @@ impl Context {
             self.snap_link_idx.take();
 
             self.node_indices_overlapping_with_mouse.clear();
+            self.culling_stats = Default::default();
+            self.diagnostics.clear();
             self.element_state_change = ElementStateChange::None as usize;
 
-            self.active_attribute = false;
+            self.active_attribute_id.take();
+            self.dropped_payload.take();
         }
 
         {
             ui.set_min_size(self.canvas_rect_screen_space.size());
-            let mut ui = ui.child_ui(
-                self.canvas_rect_screen_space,
-                egui::Layout::top_down(egui::Align::Center),
-            );
+            let layout = egui::Layout::top_down(egui::Align::Center);
+            let mut ui = match self.id {
+                Some(id) => ui.child_ui_with_id_source(self.canvas_rect_screen_space, layout, id),
+                None => ui.child_ui(self.canvas_rect_screen_space, layout),
+            };
+            let mut pin_tooltips = HashMap::new();
             {
                 let ui = &mut ui;
                 ui.set_clip_rect(
@@ -180,19 +259,41 @@ impl Context {
                 }
 
                 let links = links.into_iter().collect::<Vec<_>>();
+                let mut added_links = Vec::with_capacity(links.len());
                 for (id, start, end, args) in links {
-                    self.add_link(id, start, end, args, ui);
+                    if let Some(link_idx) = self.add_link(id, args, ui) {
+                        added_links.push((link_idx, start, end));
+                    }
                 }
 
-                let mut nodes = nodes
-                    .into_iter()
-                    .map(|x| (self.node_pool_find_or_create_index(x.id, x.pos), x))
-                    .collect::<HashMap<_, _>>();
+                let mut node_builders = HashMap::new();
+                for node_builder in nodes {
+                    let idx = self
+                        .node_pool_find_or_create_index(node_builder.id.clone(), node_builder.pos);
+                    match node_builders.entry(idx) {
+                        Entry::Occupied(_) => {
+                            self.diagnostics.push(Diagnostic::DuplicateNode(node_builder.id));
+                        }
+                        Entry::Vacant(entry) => {
+                            entry.insert(node_builder);
+                        }
+                    }
+                }
                 for idx in self.node_depth_order.clone() {
-                    if let Some(node_builder) = nodes.remove(&idx) {
-                        self.add_node(idx, node_builder, ui);
+                    if let Some(node_builder) = node_builders.remove(&idx) {
+                        if self.viewport_culling && self.node_outside_canvas(idx) {
+                            self.cull_node(idx, &node_builder);
+                        } else {
+                            self.add_node(idx, node_builder, &mut pin_tooltips, ui);
+                        }
                     }
                 }
+
+                // links are connected once all of the pins of this frame are known
+                for (link_idx, start, end) in added_links {
+                    self.connect_link(link_idx, start, end);
+                }
+                self.count_pin_links();
             }
             let response = ui.interact(
                 self.canvas_rect_screen_space,
@@ -226,10 +327,30 @@ impl Context {
                     alt_mouse_clicked && !(self.alt_mouse_clicked || self.alt_mouse_dragging);
                 self.link_detatch_with_modifier_click =
                     self.io.link_detatch_with_modifier_click.is_active(&io.modifiers);
+                // the same condition egui uses to open context menus
+                self.secondary_mouse_clicked =
+                    io.pointer.any_pressed() && io.pointer.secondary_down();
+            }
+            let (released, down) = {
+                let pointer = &ui.input().pointer;
+                (pointer.any_released(), pointer.any_down())
+            };
+            if released && self.mouse_in_canvas {
+                if let Some(payload) = drag_drop::DragPayload::take(ui.ctx()) {
+                    let pos = self.screen_space_to_grid_space(self.mouse_pos);
+                    self.dropped_payload.replace((payload, pos));
+                }
+            } else if !released && !down {
+                // a payload dropped outside of every canvas is left over once nothing is dragged
+                drag_drop::DragPayload::take(ui.ctx());
             }
             {
                 let ui = &mut ui;
-                if self.mouse_in_canvas {
+                self.build_spatial_index();
+                self.minimap_hovered = self.minimap
+                    && self.mouse_in_canvas
+                    && self.minimap_rect().contains(self.mouse_pos);
+                if self.mouse_in_canvas && !self.minimap_hovered {
                     self.resolve_occluded_pins();
                     self.resolve_hovered_pin();
 
@@ -242,11 +363,29 @@ impl Context {
                     }
                 }
 
+                if self.secondary_mouse_clicked {
+                    self.context_menu_target = if self.mouse_in_canvas && !self.minimap_hovered {
+                        Some(self.hovered_context_menu_target())
+                    } else {
+                        None
+                    };
+                }
+
                 for node_idx in self.node_depth_order.clone() {
-                    if self.nodes.in_use[node_idx] {
+                    if self.nodes.in_use[node_idx] && !self.nodes.pool[node_idx].culled {
                         self.draw_node(node_idx, ui);
                     }
                 }
+                if let Some(tooltip) = self.hovered_pin_index.and_then(|x| pin_tooltips.remove(&x))
+                {
+                    if self.click_interaction_type != ClickInteractionType::BoxSelection {
+                        egui::show_tooltip_at_pointer(
+                            ui.ctx(),
+                            ui.id().with("PinTooltip"),
+                            tooltip,
+                        );
+                    }
+                }
 
                 for (link_idx, in_use) in self.links.in_use.clone().into_iter().enumerate() {
                     if in_use {
@@ -260,6 +399,14 @@ impl Context {
 
                 self.click_interaction_update(ui);
 
+                if self.minimap {
+                    self.draw_minimap(ui);
+                }
+
+                if self.diagnostics_overlay {
+                    self.draw_diagnostics(ui);
+                }
+
                 self.node_pool_update();
                 self.pins.update();
                 self.links.update();
@@ -319,63 +466,113 @@ impl Context {
         }
     }
 
-    pub fn set_node_pos_screen_space(&mut self, node_id: usize, screen_space_pos: egui::Pos2) {
+    pub fn set_node_pos_screen_space(&mut self, node_id: T, screen_space_pos: egui::Pos2) {
         let idx = self.node_pool_find_or_create_index(node_id, None);
-        self.nodes.pool[idx].origin = self.screen_space_to_grid_space(screen_space_pos);
+        self.set_node_origin(idx, self.screen_space_to_grid_space(screen_space_pos));
     }
 
-    pub fn set_node_pos_editor_space(&mut self, node_id: usize, editor_space_pos: egui::Pos2) {
+    pub fn set_node_pos_editor_space(&mut self, node_id: T, editor_space_pos: egui::Pos2) {
         let idx = self.node_pool_find_or_create_index(node_id, None);
-        self.nodes.pool[idx].origin = self.editor_space_to_grid_spcae(editor_space_pos);
+        self.set_node_origin(idx, self.editor_space_to_grid_space(editor_space_pos));
     }
 
-    pub fn set_node_pos_grid_space(&mut self, node_id: usize, grid_pos: egui::Pos2) {
+    pub fn set_node_pos_grid_space(&mut self, node_id: T, grid_pos: egui::Pos2) {
         let idx = self.node_pool_find_or_create_index(node_id, None);
-        self.nodes.pool[idx].origin = grid_pos;
+        self.set_node_origin(idx, grid_pos);
+    }
+
+    /// Only allow links that keep the graph acyclic.
+    /// While enabled, pins refuse to snap to links that would close a cycle through the current links
+    pub fn set_acyclic(&mut self, acyclic: bool) {
+        self.acyclic = acyclic;
+    }
+
+    pub fn is_acyclic(&self) -> bool {
+        self.acyclic
+    }
+
+    /// Push overlapping nodes apart after dragged nodes are dropped, see Context::resolve_overlaps
+    pub fn set_resolve_overlaps_on_drop(&mut self, resolve: bool) {
+        self.resolve_overlaps_on_drop = resolve;
+    }
+
+    /// Skip the ui of nodes and links that are outside of the editor, enabled by default.
+    /// Culled nodes keep their last size and pin positions so links to them still connect
+    pub fn set_viewport_culling(&mut self, culling: bool) {
+        self.viewport_culling = culling;
     }
 
-    pub fn set_node_draggable(&mut self, node_id: usize, draggable: bool) {
+    /// Limit what the user can change in the editor, panning and hovering always work
+    pub fn set_interaction_mode(&mut self, mode: InteractionMode) {
+        self.interaction_mode = mode;
+    }
+
+    pub fn interaction_mode(&self) -> InteractionMode {
+        self.interaction_mode
+    }
+
+    /// The number of nodes and links that were culled in the last frame
+    pub fn culling_stats(&self) -> CullingStats {
+        self.culling_stats
+    }
+
+    /// Problems with the nodes and links submitted in the last frame.
+    /// Offending elements are skipped instead of being drawn
+    pub fn diagnostics(&self) -> &[Diagnostic<T>] {
+        &self.diagnostics
+    }
+
+    /// Outline the nodes with problems and list the diagnostics in the corner of the editor
+    pub fn set_diagnostics_overlay(&mut self, overlay: bool) {
+        self.diagnostics_overlay = overlay;
+    }
+
+    pub fn set_node_draggable(&mut self, node_id: T, draggable: bool) {
         let idx = self.node_pool_find_or_create_index(node_id, None);
         self.nodes.pool[idx].draggable = draggable;
     }
 
-    pub fn get_node_pos_screen_space(&self, node_id: usize) -> Option<egui::Pos2> {
-        self.nodes.find(node_id).map(|x| self.grid_space_to_screen_space(self.nodes.pool[x].origin))
+    pub fn get_node_pos_screen_space(&self, node_id: T) -> Option<egui::Pos2> {
+        self.nodes
+            .find(&node_id)
+            .map(|x| self.grid_space_to_screen_space(self.nodes.pool[x].origin))
     }
 
-    pub fn get_node_pos_editor_space(&self, node_id: usize) -> Option<egui::Pos2> {
-        self.nodes.find(node_id).map(|x| self.grid_space_to_editor_spcae(self.nodes.pool[x].origin))
+    pub fn get_node_pos_editor_space(&self, node_id: T) -> Option<egui::Pos2> {
+        self.nodes
+            .find(&node_id)
+            .map(|x| self.grid_space_to_editor_space(self.nodes.pool[x].origin))
     }
 
-    pub fn get_node_pos_grid_space(&self, node_id: usize) -> Option<egui::Pos2> {
-        self.nodes.find(node_id).map(|x| self.nodes.pool[x].origin)
+    pub fn get_node_pos_grid_space(&self, node_id: T) -> Option<egui::Pos2> {
+        self.nodes.find(&node_id).map(|x| self.nodes.pool[x].origin)
     }
 
     /// Check if there is a node that is hovered by the pointer
-    pub fn node_hovered(&self) -> Option<usize> {
-        self.hovered_node_index.map(|x| self.nodes.pool[x].id)
+    pub fn node_hovered(&self) -> Option<T> {
+        self.hovered_node_index.map(|x| self.nodes.pool[x].id.clone())
     }
 
     /// Check if there is a link that is hovered by the pointer
-    pub fn link_hovered(&self) -> Option<usize> {
-        self.hovered_link_idx.map(|x| self.links.pool[x].id)
+    pub fn link_hovered(&self) -> Option<T> {
+        self.hovered_link_idx.map(|x| self.links.pool[x].id.clone())
     }
 
     /// Check if there is a pin that is hovered by the pointer
-    pub fn pin_hovered(&self) -> Option<usize> {
-        self.hovered_pin_index.map(|x| self.pins.pool[x].id)
+    pub fn pin_hovered(&self) -> Option<T> {
+        self.hovered_pin_index.map(|x| self.pins.pool[x].id.clone())
     }
 
     pub fn num_selected_nodes(&self) -> usize {
-        self.selected_link_indices.len()
+        self.selected_node_indices.len()
     }
 
-    pub fn get_selected_nodes(&self) -> Vec<usize> {
-        self.selected_node_indices.iter().map(|x| self.nodes.pool[*x].id).collect()
+    pub fn get_selected_nodes(&self) -> Vec<T> {
+        self.selected_node_indices.iter().map(|x| self.nodes.pool[*x].id.clone()).collect()
     }
 
-    pub fn get_selected_links(&self) -> Vec<usize> {
-        self.selected_link_indices.iter().map(|x| self.links.pool[*x].id).collect()
+    pub fn get_selected_links(&self) -> Vec<T> {
+        self.selected_link_indices.iter().map(|x| self.links.pool[*x].id.clone()).collect()
     }
 
     pub fn clear_node_selection(&mut self) {
@@ -386,32 +583,46 @@ impl Context {
         self.selected_link_indices.clear()
     }
 
+    /// What was under the pointer when the editor was last right-clicked.
+    /// Used to fill a context menu on the Response returned by Context::show, see also NodeEditor::node_context_menu
+    pub fn context_menu_target(&self) -> Option<&ContextMenuTarget<T>> {
+        self.context_menu_target.as_ref()
+    }
+
     /// Check if an attribute is currently being interacted with
-    pub fn active_attribute(&self) -> Option<usize> {
-        if self.active_attribute {
-            Some(self.active_attribute_id)
-        } else {
-            None
-        }
+    pub fn active_attribute(&self) -> Option<T> {
+        self.active_attribute_id.clone()
     }
 
     /// Has a new link been created from a pin?
-    pub fn link_started(&self) -> Option<usize> {
+    pub fn link_started(&self) -> Option<T> {
         if (self.element_state_change & ElementStateChange::LinkStarted as usize) != 0 {
-            Some(self.pins.pool[self.click_interaction_state.link_creation.start_pin_idx].id)
+            Some(
+                self.pins.pool[self.click_interaction_state.link_creation.start_pin_idx].id.clone(),
+            )
         } else {
             None
         }
     }
 
+    /// The payload of a drag_source dropped on the canvas this frame if it has type P,
+    /// and where it was dropped in grid space
+    pub fn dropped_payload<P: std::any::Any>(&self) -> Option<(&P, egui::Pos2)> {
+        self.dropped_payload
+            .as_ref()
+            .and_then(|(payload, pos)| Some((payload.downcast_ref()?, *pos)))
+    }
+
     /// Has a link been dropped? if including_detached_links then links that were detached then dropped are included
-    pub fn link_dropped(&self, including_detached_links: bool) -> Option<usize> {
+    pub fn link_dropped(&self, including_detached_links: bool) -> Option<T> {
         if (self.element_state_change & ElementStateChange::LinkDropped as usize) != 0
             && (including_detached_links
                 || self.click_interaction_state.link_creation.link_creation_type
                     != LinkCreationType::FromDetach)
         {
-            Some(self.pins.pool[self.click_interaction_state.link_creation.start_pin_idx].id)
+            Some(
+                self.pins.pool[self.click_interaction_state.link_creation.start_pin_idx].id.clone(),
+            )
         } else {
             None
         }
@@ -419,17 +630,17 @@ impl Context {
 
     /// Has a new link been created?
     /// -> Option<start_pin, end_pin created_from_snap>
-    pub fn link_created(&self) -> Option<(usize, usize, bool)> {
+    pub fn link_created(&self) -> Option<(T, T, bool)> {
         if (self.element_state_change & ElementStateChange::LinkCreated as usize) != 0 {
             let (start_pin_id, end_pin_id) = {
                 let start_pin =
                     &self.pins.pool[self.click_interaction_state.link_creation.start_pin_idx];
                 let end_pin = &self.pins.pool
                     [self.click_interaction_state.link_creation.end_pin_index.unwrap()];
-                if start_pin.kind == AttributeType::Output {
-                    (start_pin.id, end_pin.id)
+                if start_pin.kind.is_link_reversed(end_pin.kind) {
+                    (end_pin.id.clone(), start_pin.id.clone())
                 } else {
-                    (end_pin.id, start_pin.id)
+                    (start_pin.id.clone(), end_pin.id.clone())
                 }
             };
             let created_from_snap =
@@ -442,7 +653,7 @@ impl Context {
 
     /// Has a new link been created? Includes start and end node
     /// -> Option<start_pin, start_node, end_pin, end_node created_from_snap>
-    pub fn link_created_node(&self) -> Option<(usize, usize, usize, usize, bool)> {
+    pub fn link_created_node(&self) -> Option<(T, T, T, T, bool)> {
         if (self.element_state_change & ElementStateChange::LinkCreated as usize) != 0 {
             let (start_pin_id, start_node_id, end_pin_id, end_node_id) = {
                 let start_pin =
@@ -451,11 +662,18 @@ impl Context {
                     [self.click_interaction_state.link_creation.end_pin_index.unwrap()];
                 let start_node = &self.nodes.pool[start_pin.parent_node_idx];
                 let end_node = &self.nodes.pool[end_pin.parent_node_idx];
-                if start_pin.kind == AttributeType::Output {
-                    (start_pin.id, start_node.id, end_pin.id, end_node.id)
-                } else {
-                    (end_pin.id, end_node.id, start_pin.id, start_node.id)
-                }
+                let (start_pin, start_node, end_pin, end_node) =
+                    if start_pin.kind.is_link_reversed(end_pin.kind) {
+                        (end_pin, end_node, start_pin, start_node)
+                    } else {
+                        (start_pin, start_node, end_pin, end_node)
+                    };
+                (
+                    start_pin.id.clone(),
+                    start_node.id.clone(),
+                    end_pin.id.clone(),
+                    end_node.id.clone(),
+                )
             };
             let created_from_snap =
                 self.click_interaction_type == ClickInteractionType::LinkCreation;
@@ -472,8 +690,34 @@ impl Context {
     }
 
     // Was an existing link detached?
-    pub fn link_destroyed(&self) -> Option<usize> {
-        self.deleted_link_idx
+    pub fn link_destroyed(&self) -> Option<T> {
+        self.deleted_link_idx.map(|x| self.links.pool[x].id.clone())
+    }
+
+    /// Screen space is the space of the egui Ui, editor space has its origin at the top left of the canvas
+    /// and grid space is editor space moved by the panning, nodes are placed in grid space
+    pub fn screen_space_to_grid_space(&self, v: egui::Pos2) -> egui::Pos2 {
+        v - self.canvas_origin_screen_space - self.panning
+    }
+
+    pub fn grid_space_to_screen_space(&self, v: egui::Pos2) -> egui::Pos2 {
+        v + self.canvas_origin_screen_space + self.panning
+    }
+
+    pub fn grid_space_to_editor_space(&self, v: egui::Pos2) -> egui::Pos2 {
+        v + self.panning
+    }
+
+    pub fn editor_space_to_grid_space(&self, v: egui::Pos2) -> egui::Pos2 {
+        v - self.panning
+    }
+
+    pub fn editor_space_to_screen_space(&self, v: egui::Pos2) -> egui::Pos2 {
+        v + self.canvas_origin_screen_space
+    }
+
+    pub fn screen_space_to_editor_space(&self, v: egui::Pos2) -> egui::Pos2 {
+        v - self.canvas_origin_screen_space
     }
 
     pub fn get_panning(&self) -> egui::Vec2 {
@@ -484,12 +728,88 @@ impl Context {
         self.panning = panning;
     }
 
-    pub fn get_node_dimensions(&self, id: usize) -> Option<egui::Vec2> {
-        self.nodes.find(id).map(|x| self.nodes.pool[x].rect.size())
+    pub fn get_node_dimensions(&self, id: T) -> Option<egui::Vec2> {
+        self.nodes.find(&id).map(|x| self.nodes.pool[x].rect.size())
+    }
+
+    /// The ids of the nodes shown in the last frame
+    pub fn node_ids(&self) -> impl Iterator<Item = &T> {
+        self.nodes.ids()
+    }
+
+    /// The ids of the pins shown in the last frame
+    pub fn pin_ids(&self) -> impl Iterator<Item = &T> {
+        self.pins.ids()
+    }
+
+    /// The ids of the links shown in the last frame
+    pub fn link_ids(&self) -> impl Iterator<Item = &T> {
+        self.links.ids()
+    }
+
+    pub fn get_node_rect_screen_space(&self, id: T) -> Option<egui::Rect> {
+        self.nodes.find(&id).map(|x| self.nodes.pool[x].rect)
+    }
+
+    /// The rect of the title bar of a node in screen space, None if the node has no title
+    pub fn get_node_title_rect_screen_space(&self, id: T) -> Option<egui::Rect> {
+        self.nodes
+            .find(&id)
+            .map(|x| &self.nodes.pool[x])
+            .filter(|x| x.title_bar_content_rect.height() > 0.0)
+            .map(|x| x.get_node_title_rect())
+    }
+
+    pub fn get_pin_pos_screen_space(&self, id: T) -> Option<egui::Pos2> {
+        self.pins.find(&id).map(|x| self.pins.pool[x].pos)
+    }
+
+    pub fn get_pin_kind(&self, id: T) -> Option<AttributeType> {
+        self.pins.find(&id).map(|x| self.pins.pool[x].kind)
+    }
+
+    /// The number of links of a pin in the last frame
+    pub fn pin_link_count(&self, id: T) -> Option<usize> {
+        self.pins.find(&id).map(|x| self.pins.pool[x].link_count)
+    }
+
+    /// The slot of a pin added by NodeConstructor::with_variadic_attribute
+    pub fn get_pin_slot(&self, id: T) -> Option<usize> {
+        self.pins.find(&id).and_then(|x| self.pins.pool[x].slot.as_ref().map(|x| x.1))
+    }
+
+    /// The variadic attribute slots whose only link was destroyed in the last frame, with the id of their attribute.
+    /// The app should remove these slots, see NodeConstructor::with_variadic_attribute
+    pub fn variadic_slots_removed(&self) -> Vec<(T, usize)> {
+        let link = match self.deleted_link_idx {
+            Some(idx) => &self.links.pool[idx],
+            None => return Vec::new(),
+        };
+        [link.start_pin_index, link.end_pin_index]
+            .iter()
+            .map(|x| &self.pins.pool[*x])
+            .filter(|x| x.link_count == 1)
+            .filter_map(|x| x.slot.clone())
+            .collect()
+    }
+
+    /// The curve of a link in screen space, from the output pin through the two control points to the input pin
+    pub fn get_link_bezier(&self, id: T) -> Option<geometry::BezierCurve> {
+        let link = &self.links.pool[self.links.find(&id)?];
+        let start_pin = &self.pins.pool[link.start_pin_index];
+        let end_pin = &self.pins.pool[link.end_pin_index];
+        Some(LinkBezierData::bezier(
+            start_pin.pos,
+            end_pin.pos,
+            start_pin.side.normal(),
+            end_pin.side.normal(),
+            start_pin.kind,
+            end_pin.kind,
+        ))
     }
 }
 
-impl Context {
+impl<T: Hash + Eq + Clone> Context<T> {
     fn add_node<'a>(
         &mut self,
         idx: usize,
@@ -499,94 +819,197 @@ impl Context {
             attributes,
             pos: _,
             args,
-        }: NodeConstructor<'a>,
+        }: NodeConstructor<'a, T>,
+        pin_tooltips: &mut HashMap<usize, pin::PinTooltip<'a>>,
         ui: &mut egui::Ui,
     ) {
         let node = &mut self.nodes.pool[idx];
+        node.culled = false;
+        node.pin_indices.clear();
         self.style.format_node(node, args);
         node.background_shape.replace(ui.painter().add(egui::Shape::Noop));
-        node.id = id;
         let node_origin = node.origin;
         let node_size = node.size;
         let title_space = node.layout_style.padding.y;
 
-        let response = ui.allocate_ui_at_rect(
+        // scoped by the node id, so widgets with the same id source in different nodes don't clash
+        let mut node_ui = ui.child_ui_with_id_source(
             egui::Rect::from_min_size(self.grid_space_to_screen_space(node_origin), node_size),
-            |ui| {
-                let mut title_info = None;
-                if let Some(title) = title {
-                    let titlebar_shape = ui.painter().add(egui::Shape::Noop);
-                    let response = ui.allocate_ui(ui.available_size(), title);
-                    let title_bar_content_rect = response.response.rect;
-                    title_info.replace((titlebar_shape, title_bar_content_rect));
-                    ui.add_space(title_space);
-                }
-                let outline_shape = ui.painter().add(egui::Shape::Noop);
-                for (id, kind, args, attribute) in attributes {
-                    let response = ui.allocate_ui(ui.available_size(), attribute);
-                    let shape = ui.painter().add(egui::Shape::Noop);
-                    let response = response.response.union(response.inner);
-                    self.add_attribute(id, kind, args, response, idx, shape);
-                }
-                (title_info, outline_shape)
-            },
+            *ui.layout(),
+            &id,
         );
+        self.nodes.pool[idx].id = id;
+        let (title_info, outline_shape) = {
+            let ui = &mut node_ui;
+            let mut title_info = None;
+            if let Some(title) = title {
+                let titlebar_shape = ui.painter().add(egui::Shape::Noop);
+                let response = ui.allocate_ui(ui.available_size(), title);
+                let title_bar_content_rect = response.response.rect;
+                title_info.replace((titlebar_shape, title_bar_content_rect));
+                ui.add_space(title_space);
+            }
+            let outline_shape = ui.painter().add(egui::Shape::Noop);
+            for (id, kind, mut args, slot, attribute) in attributes {
+                let response = ui.allocate_ui(ui.available_size(), attribute);
+                let shape = ui.painter().add(egui::Shape::Noop);
+                let response = response.response.union(response.inner);
+                let tooltip = args.tooltip.take();
+                if let Some(pin_idx) = self.add_attribute(id, kind, args, response, idx, shape) {
+                    self.pins.pool[pin_idx].slot = slot;
+                    if let Some(tooltip) = tooltip {
+                        pin_tooltips.insert(pin_idx, tooltip);
+                    }
+                }
+            }
+            (title_info, outline_shape)
+        };
+        let response = ui.interact(node_ui.min_rect(), node_ui.id(), egui::Sense::hover());
         let node = &mut self.nodes.pool[idx];
-        let (title_info, outline_shape) = response.inner;
         if let Some((titlebar_shape, title_bar_content_rect)) = title_info {
             node.titlebar_shape.replace(titlebar_shape);
             node.title_bar_content_rect = title_bar_content_rect;
         }
         node.outline_shape.replace(outline_shape);
-        node.rect = response.response.rect.expand2(node.layout_style.padding);
-        if response.response.hovered() {
+        node.rect = response.rect.expand2(node.layout_style.padding);
+        if response.hovered() {
             self.node_indices_overlapping_with_mouse.push(idx);
         }
     }
 
+    /// Checks if a node that has been drawn before is entirely outside of the canvas
+    fn node_outside_canvas(&self, idx: usize) -> bool {
+        let node = &self.nodes.pool[idx];
+        if node.rect.width() <= 0.0 || node.rect.height() <= 0.0 {
+            return false;
+        }
+        let delta = self.grid_space_to_screen_space(node.origin)
+            - node.layout_style.padding
+            - node.rect.min;
+        // pins stick out of the node, by as far as they were placed last frame
+        let rect =
+            node.pin_indices.iter().map(|x| &self.pins.pool[*x]).fold(node.rect, |rect, pin| {
+                rect.union(egui::Rect::from_center_size(
+                    pin.pos,
+                    egui::Vec2::splat(2.0 * pin.hover_radius),
+                ))
+            });
+        !rect.translate(delta).intersects(self.canvas_rect_screen_space)
+    }
+
+    /// Keeps a node without running its ui, moving its last known layout to where the node is now.
+    /// Only the pins the node still submits this frame are kept
+    fn cull_node(&mut self, idx: usize, node_builder: &NodeConstructor<T>) {
+        let screen_origin = self.grid_space_to_screen_space(self.nodes.pool[idx].origin);
+        let submitted: HashSet<&T> = node_builder
+            .attributes
+            .iter()
+            .filter(|x| x.1 != AttributeType::None)
+            .map(|x| &x.0)
+            .collect();
+        let pins = &self.pins;
+        let node = &mut self.nodes.pool[idx];
+        node.pin_indices.retain(|x| {
+            let pin = &pins.pool[*x];
+            pin.parent_node_idx == idx && !pins.in_use[*x] && submitted.contains(&pin.id)
+        });
+        let delta = screen_origin - node.layout_style.padding - node.rect.min;
+        node.culled = true;
+        node.rect = node.rect.translate(delta);
+        node.title_bar_content_rect = node.title_bar_content_rect.translate(delta);
+        for pin_idx in node.pin_indices.iter() {
+            let pin = &mut self.pins.pool[*pin_idx];
+            pin.attribute_rect = pin.attribute_rect.translate(delta);
+            pin.pos += delta;
+            self.pins.in_use[*pin_idx] = true;
+        }
+        self.culling_stats.nodes += 1;
+    }
+
     fn add_attribute(
         &mut self,
-        id: usize,
+        id: T,
         kind: AttributeType,
-        args: PinArgs,
+        args: PinArgs<'_>,
         response: egui::Response,
         node_idx: usize,
         shape: egui::layers::ShapeIdx,
-    ) {
+    ) -> Option<usize> {
+        let mut added_pin_idx = None;
         if kind != AttributeType::None {
-            let pin_idx = self.pins.find_or_create_index(id);
-            let pin = &mut self.pins.pool[pin_idx];
-            pin.id = id;
-            pin.parent_node_idx = node_idx;
-            pin.kind = kind;
-            pin.shape_gui.replace(shape);
-            self.style.format_pin(pin, args, self.current_attribute_flags);
-            self.pins.pool[pin_idx].attribute_rect = response.rect;
-            self.nodes.pool[node_idx].pin_indices.push(pin_idx);
+            match self.pins.find(&id) {
+                Some(pin_idx) if self.pins.in_use[pin_idx] => {
+                    self.diagnostics.push(Diagnostic::DuplicatePin(id.clone()));
+                }
+                _ => {
+                    let pin_idx = self.pins.find_or_create_index(id.clone());
+                    let pin = &mut self.pins.pool[pin_idx];
+                    pin.parent_node_idx = node_idx;
+                    pin.kind = kind;
+                    pin.shape_gui.replace(shape);
+                    self.style.format_pin(pin, args, self.current_attribute_flags);
+                    self.pins.pool[pin_idx].attribute_rect = response.rect;
+                    self.nodes.pool[node_idx].pin_indices.push(pin_idx);
+                    added_pin_idx.replace(pin_idx);
+                }
+            }
         }
 
         if response.is_pointer_button_down_on() {
-            self.active_attribute = true;
-            self.active_attribute_id = id;
+            self.active_attribute_id.replace(id);
             self.interactive_node_index.replace(node_idx);
         }
+        added_pin_idx
     }
 
-    fn add_link(
-        &mut self,
-        id: usize,
-        start_attr_id: usize,
-        end_attr_id: usize,
-        args: LinkArgs,
-        ui: &mut egui::Ui,
-    ) {
+    /// Reserves the shape of a link below the nodes, the link is connected to its pins by connect_link
+    fn add_link(&mut self, id: T, args: LinkArgs, ui: &mut egui::Ui) -> Option<usize> {
+        if let Some(link_idx) = self.links.find(&id) {
+            if self.links.in_use[link_idx] {
+                self.diagnostics.push(Diagnostic::DuplicateLink(id));
+                return None;
+            }
+        }
         let link_idx = self.links.find_or_create_index(id);
         let link = &mut self.links.pool[link_idx];
-        link.id = id;
-        link.start_pin_index = self.pins.find_or_create_index(start_attr_id);
-        link.end_pin_index = self.pins.find_or_create_index(end_attr_id);
         link.shape.replace(ui.painter().add(egui::Shape::Noop));
         self.style.format_link(link, args);
+        Some(link_idx)
+    }
+
+    /// Counts the links of every pin once all links of this frame are connected
+    fn count_pin_links(&mut self) {
+        for pin in self.pins.pool.iter_mut() {
+            pin.link_count = 0;
+        }
+        for (link, in_use) in self.links.pool.iter().zip(self.links.in_use.iter()) {
+            if *in_use {
+                self.pins.pool[link.start_pin_index].link_count += 1;
+                self.pins.pool[link.end_pin_index].link_count += 1;
+            }
+        }
+    }
+
+    fn connect_link(&mut self, link_idx: usize, start_attr_id: T, end_attr_id: T) {
+        let start_pin_index = self.pins.find(&start_attr_id).filter(|x| self.pins.in_use[*x]);
+        let end_pin_index = self.pins.find(&end_attr_id).filter(|x| self.pins.in_use[*x]);
+        let (start_pin_index, end_pin_index) = match (start_pin_index, end_pin_index) {
+            (Some(start_pin_index), Some(end_pin_index)) => (start_pin_index, end_pin_index),
+            (start_pin_index, _) => {
+                let pin = if start_pin_index.is_none() {
+                    start_attr_id
+                } else {
+                    end_attr_id
+                };
+                let link = self.links.pool[link_idx].id.clone();
+                self.diagnostics.push(Diagnostic::DanglingLink { link, pin });
+                self.links.in_use[link_idx] = false;
+                return;
+            }
+        };
+        let link = &mut self.links.pool[link_idx];
+        link.start_pin_index = start_pin_index;
+        link.end_pin_index = end_pin_index;
 
         if (self.click_interaction_type == ClickInteractionType::LinkCreation
             && (self.pins.pool[link.end_pin_index].flags
@@ -602,6 +1025,10 @@ impl Context {
         }
     }
 
+    fn set_node_origin(&mut self, node_idx: usize, grid_pos: egui::Pos2) {
+        self.nodes.pool[node_idx].origin = grid_pos;
+    }
+
     fn lookup_style_var(&mut self, item: StyleVar) -> &mut f32 {
         match item {
             StyleVar::GridSpacing => &mut self.style.grid_spacing,
@@ -610,6 +1037,7 @@ impl Context {
             StyleVar::NodePaddingVertical => &mut self.style.node_padding_vertical,
             StyleVar::NodeBorderThickness => &mut self.style.node_border_thickness,
             StyleVar::LinkThickness => &mut self.style.link_thickness,
+            #[allow(deprecated)]
             StyleVar::LinkLineSegmentsPerLength => &mut self.style.link_line_segments_per_length,
             StyleVar::LinkHoverDistance => &mut self.style.link_hover_distance,
             StyleVar::PinCircleRadius => &mut self.style.pin_circle_radius,
@@ -618,6 +1046,8 @@ impl Context {
             StyleVar::PinLineThickness => &mut self.style.pin_line_thickness,
             StyleVar::PinHoverRadius => &mut self.style.pin_hover_radius,
             StyleVar::PinOffset => &mut self.style.pin_offset,
+            StyleVar::NodeSeparation => &mut self.style.node_separation,
+            StyleVar::LinkTessellationTolerance => &mut self.style.link_tessellation_tolerance,
         }
     }
 
@@ -647,51 +1077,101 @@ impl Context {
         }
     }
 
-    fn screen_space_to_grid_space(&self, v: egui::Pos2) -> egui::Pos2 {
-        v - self.canvas_origin_screen_space - self.panning
-    }
-
-    fn grid_space_to_screen_space(&self, v: egui::Pos2) -> egui::Pos2 {
-        v + self.canvas_origin_screen_space + self.panning
+    fn get_screen_space_pin_coordinates(&self, pin: &PinData<T>) -> egui::Pos2 {
+        let parent_node_rect = self.nodes.pool[pin.parent_node_idx].rect;
+        self.style.get_screen_space_pin_coordinates(
+            &parent_node_rect,
+            &pin.attribute_rect,
+            pin.side,
+            pin.side_offset,
+        )
     }
 
-    fn grid_space_to_editor_spcae(&self, v: egui::Pos2) -> egui::Pos2 {
-        v + self.panning
-    }
+    /// Indexes the nodes, pins and links of this frame for hit testing
+    fn build_spatial_index(&mut self) {
+        self.node_grid.clear();
+        self.pin_grid.clear();
+        self.link_grid.clear();
 
-    fn editor_space_to_grid_spcae(&self, v: egui::Pos2) -> egui::Pos2 {
-        v - self.panning
-    }
+        for idx in 0..self.nodes.pool.len() {
+            if !self.nodes.in_use[idx] {
+                continue;
+            }
+            self.node_grid.insert(idx, self.nodes.pool[idx].rect);
+            for pin_idx in self.nodes.pool[idx].pin_indices.clone() {
+                let pin_pos = self.get_screen_space_pin_coordinates(&self.pins.pool[pin_idx]);
+                self.pins.pool[pin_idx].pos = pin_pos;
+                self.pin_grid.insert(
+                    pin_idx,
+                    egui::Rect::from_center_size(
+                        pin_pos,
+                        egui::Vec2::splat(2.0 * self.pins.pool[pin_idx].hover_radius),
+                    ),
+                );
+            }
+        }
 
-    fn editor_space_to_screen_space(&self, v: egui::Pos2) -> egui::Pos2 {
-        v + self.canvas_origin_screen_space
+        // links are also hovered through their pins, so they need to cover the pin hover areas
+        for idx in 0..self.links.pool.len() {
+            if !self.links.in_use[idx] {
+                continue;
+            }
+            let link = &self.links.pool[idx];
+            let pin_hover_radius = self.pins.pool[link.start_pin_index]
+                .hover_radius
+                .max(self.pins.pool[link.end_pin_index].hover_radius);
+            let link_hover_distance = self.style.link_hover_distance.max(pin_hover_radius);
+            let link_rect = self.link_renderable(idx).rect.expand(link_hover_distance);
+            self.link_grid.insert(idx, link_rect);
+        }
     }
 
-    fn get_screen_space_pin_coordinates(&self, pin: &PinData) -> egui::Pos2 {
-        let parent_node_rect = self.nodes.pool[pin.parent_node_idx].rect;
-        self.style.get_screen_space_pin_coordinates(
-            &parent_node_rect,
-            &pin.attribute_rect,
-            pin.kind,
-        )
+    /// The flattened curve of a link, only recomputed when one of its pins has moved
+    fn link_renderable(&mut self, link_idx: usize) -> &LinkBezierData {
+        let link = &self.links.pool[link_idx];
+        let start_pin = &self.pins.pool[link.start_pin_index];
+        let end_pin = &self.pins.pool[link.end_pin_index];
+        let key = LinkRenderableKey {
+            start: start_pin.pos,
+            end: end_pin.pos,
+            start_normal: start_pin.side.normal(),
+            end_normal: end_pin.side.normal(),
+            start_type: start_pin.kind,
+            end_type: end_pin.kind,
+            tolerance: self.style.link_tessellation_tolerance,
+        };
+        let renderable = &mut self.links.pool[link_idx].renderable;
+        match renderable {
+            Some(link_data) if link_data.key == key => (),
+            _ => {
+                renderable.replace(LinkBezierData::get_link_renderable(key));
+            }
+        }
+        renderable.as_ref().unwrap()
     }
 
     fn resolve_occluded_pins(&mut self) {
         self.occluded_pin_indices.clear();
-        let depth_stack = &self.node_depth_order;
-        if depth_stack.len() < 2 {
+        if self.node_depth_order.len() < 2 {
             return;
         }
 
-        for depth_idx in 0..(depth_stack.len() - 1) {
-            let node_below = &self.nodes.pool[depth_stack[depth_idx]];
-            for next_depth in &depth_stack[(depth_idx + 1)..(depth_stack.len())] {
-                let rect_above = self.nodes.pool[*next_depth].rect;
-                for idx in node_below.pin_indices.iter() {
-                    let pin_pos = self.pins.pool[*idx].pos;
-                    if rect_above.contains(pin_pos) {
-                        self.occluded_pin_indices.push(*idx);
-                    }
+        let mut depth = vec![0; self.nodes.pool.len()];
+        for (depth_idx, node_idx) in self.node_depth_order.iter().enumerate() {
+            depth[*node_idx] = depth_idx;
+        }
+
+        let mut nodes_at_pin = Vec::new();
+        for node_idx in self.node_depth_order.iter() {
+            for pin_idx in self.nodes.pool[*node_idx].pin_indices.iter() {
+                let pin_pos = self.pins.pool[*pin_idx].pos;
+                self.node_grid.query_point(pin_pos, &mut nodes_at_pin);
+                let occluded = nodes_at_pin.iter().any(|above| {
+                    depth[*above] > depth[*node_idx]
+                        && self.nodes.pool[*above].rect.contains(pin_pos)
+                });
+                if occluded {
+                    self.occluded_pin_indices.insert(*pin_idx);
                 }
             }
         }
@@ -701,15 +1181,16 @@ impl Context {
         let mut smallest_distance = f32::MAX;
         self.hovered_pin_index.take();
 
-        let hover_radius_sqr = self.style.pin_hover_radius.powi(2);
-        for idx in 0..self.pins.pool.len() {
+        let mut candidates = Vec::new();
+        self.pin_grid.query_point(self.mouse_pos, &mut candidates);
+        for idx in candidates {
             if !self.pins.in_use[idx] || self.occluded_pin_indices.contains(&idx) {
                 continue;
             }
 
-            let pin_pos = self.pins.pool[idx].pos;
-            let distance_sqr = (pin_pos - self.mouse_pos).length_sq();
-            if distance_sqr < hover_radius_sqr && distance_sqr < smallest_distance {
+            let pin = &self.pins.pool[idx];
+            let distance_sqr = (pin.pos - self.mouse_pos).length_sq();
+            if distance_sqr < pin.hover_radius.powi(2) && distance_sqr < smallest_distance {
                 smallest_distance = distance_sqr;
                 self.hovered_pin_index.replace(idx);
             }
@@ -743,7 +1224,9 @@ impl Context {
         let mut smallest_distance = f32::MAX;
         self.hovered_link_idx.take();
 
-        for idx in 0..self.links.pool.len() {
+        let mut candidates = Vec::new();
+        self.link_grid.query_point(self.mouse_pos, &mut candidates);
+        for idx in candidates {
             if !self.links.in_use[idx] {
                 continue;
             }
@@ -756,22 +1239,14 @@ impl Context {
                 return;
             }
 
-            let start_pin = &self.pins.pool[link.start_pin_index];
-            let end_pin = &self.pins.pool[link.end_pin_index];
-
-            let link_data = LinkBezierData::get_link_renderable(
-                start_pin.pos,
-                end_pin.pos,
-                start_pin.kind,
-                self.style.link_line_segments_per_length,
-            );
-            let link_rect = link_data
-                .bezier
-                .get_containing_rect_for_bezier_curve(self.style.link_hover_distance);
+            let mouse_pos = self.mouse_pos;
+            let link_hover_distance = self.style.link_hover_distance;
+            let link_data = self.link_renderable(idx);
+            let link_rect = link_data.rect.expand(link_hover_distance);
 
-            if link_rect.contains(self.mouse_pos) {
-                let distance = link_data.get_distance_to_cubic_bezier(&self.mouse_pos);
-                if distance < self.style.link_hover_distance && distance < smallest_distance {
+            if link_rect.contains(mouse_pos) {
+                let distance = link_data.get_distance_to_cubic_bezier(&mouse_pos);
+                if distance < link_hover_distance && distance < smallest_distance {
                     smallest_distance = distance;
                     self.hovered_link_idx.replace(idx);
                 }
@@ -780,16 +1255,13 @@ impl Context {
     }
 
     fn draw_link(&mut self, link_idx: usize, ui: &mut egui::Ui) {
-        let link = &mut self.links.pool[link_idx];
-        let start_pin = &self.pins.pool[link.start_pin_index];
-        let end_pin = &self.pins.pool[link.end_pin_index];
-        let link_data = LinkBezierData::get_link_renderable(
-            start_pin.pos,
-            end_pin.pos,
-            start_pin.kind,
-            self.style.link_line_segments_per_length,
-        );
-        let link_shape = link.shape.take().unwrap();
+        let link_shape = self.links.pool[link_idx].shape.take().unwrap();
+        let link_thickness = self.style.link_thickness;
+        let link_rect = self.link_renderable(link_idx).rect.expand(link_thickness);
+        if self.viewport_culling && !link_rect.intersects(self.canvas_rect_screen_space) {
+            self.culling_stats.links += 1;
+            return;
+        }
         let link_hovered = self.hovered_link_idx == Some(link_idx)
             && self.click_interaction_type != ClickInteractionType::BoxSelection;
 
@@ -809,10 +1281,8 @@ impl Context {
             link_color = link.color_style.hovered;
         }
 
-        ui.painter().set(
-            link_shape,
-            link_data.draw((self.style.link_thickness, link_color)),
-        );
+        let link_data = self.link_renderable(link_idx);
+        ui.painter().set(link_shape, link_data.draw((link_thickness, link_color)));
     }
 
     fn draw_node(&mut self, node_idx: usize, ui: &mut egui::Ui) {
@@ -880,19 +1350,46 @@ impl Context {
         pin.pos = self.style.get_screen_space_pin_coordinates(
             &parent_node_rect,
             &pin.attribute_rect,
-            pin.kind,
+            pin.side,
+            pin.side_offset,
         );
 
-        let mut pin_color = pin.color_style.background;
+        let connected = pin.link_count > 0;
+        let mut pin_color = if connected {
+            pin.color_style.connected
+        } else {
+            pin.color_style.background
+        };
 
         let pin_hovered = self.hovered_pin_index == Some(pin_idx)
             && self.click_interaction_type != ClickInteractionType::BoxSelection;
-        let pin_shape = pin.shape;
+        let pin_shape = match &pin.connected_shape {
+            _ if !connected => pin.shape.clone(),
+            Some(shape) => shape.clone(),
+            None => pin.shape.filled(),
+        };
+        let pin_side = pin.side;
         let pin_pos = pin.pos;
-        let pin_shape_gui = pin
-            .shape_gui
-            .take()
-            .expect("Unable to take pin shape. Perhaps your pin id is not unique?");
+        let pin_shape_gui = match pin.shape_gui.take() {
+            Some(shape_gui) => shape_gui,
+            None => return,
+        };
+
+        if let Some(label) = pin.label.as_ref() {
+            let align = match pin.side {
+                PinSide::Left => egui::Align2::RIGHT_CENTER,
+                PinSide::Right => egui::Align2::LEFT_CENTER,
+                PinSide::Top => egui::Align2::CENTER_BOTTOM,
+                PinSide::Bottom => egui::Align2::CENTER_TOP,
+            };
+            ui.painter().text(
+                pin_pos + pin.side.normal() * pin.hover_radius,
+                align,
+                label,
+                egui::TextStyle::Small,
+                ui.visuals().text_color(),
+            );
+        }
 
         if pin_hovered {
             self.hovered_pin_flags = pin.flags;
@@ -903,7 +1400,71 @@ impl Context {
             }
         }
 
-        self.style.draw_pin_shape(pin_pos, pin_shape, pin_color, pin_shape_gui, ui);
+        let state = PinShapeState {
+            pos: pin_pos,
+            color: pin_color,
+            hovered: pin_hovered,
+            connected,
+            side: pin_side,
+        };
+        self.style.draw_pin_shape(&pin_shape, state, pin_shape_gui, ui);
+    }
+
+    fn draw_diagnostics(&self, ui: &mut egui::Ui) {
+        let color = egui::Color32::RED;
+        let mut counts = [0; 4];
+        for diagnostic in self.diagnostics.iter() {
+            let (kind, node_idx) = match diagnostic {
+                Diagnostic::DuplicateNode(id) => (0, self.nodes.find(id)),
+                Diagnostic::DuplicatePin(id) => (
+                    1,
+                    self.pins.find(id).map(|x| self.pins.pool[x].parent_node_idx),
+                ),
+                Diagnostic::DuplicateLink(_) => (2, None),
+                Diagnostic::DanglingLink { .. } => (3, None),
+            };
+            counts[kind] += 1;
+            if let Some(node_idx) = node_idx {
+                let node = &self.nodes.pool[node_idx];
+                ui.painter().rect_stroke(
+                    node.rect,
+                    node.layout_style.corner_rounding,
+                    (2.0, color),
+                );
+            }
+        }
+
+        let labels = [
+            "duplicate node ids",
+            "duplicate pin ids",
+            "duplicate link ids",
+            "dangling links",
+        ];
+        let mut pos = self.canvas_rect_screen_space.min + egui::vec2(4.0, 4.0);
+        for (count, label) in counts.iter().zip(labels.iter()) {
+            if *count > 0 {
+                let rect = ui.painter().text(
+                    pos,
+                    egui::Align2::LEFT_TOP,
+                    format!("{}: {}", label, count),
+                    egui::TextStyle::Monospace,
+                    color,
+                );
+                pos.y = rect.max.y;
+            }
+        }
+    }
+
+    fn hovered_context_menu_target(&self) -> ContextMenuTarget<T> {
+        if let Some(idx) = self.hovered_pin_index {
+            ContextMenuTarget::Pin(self.pins.pool[idx].id.clone())
+        } else if let Some(idx) = self.hovered_node_index {
+            ContextMenuTarget::Node(self.nodes.pool[idx].id.clone())
+        } else if let Some(idx) = self.hovered_link_idx {
+            ContextMenuTarget::Link(self.links.pool[idx].id.clone())
+        } else {
+            ContextMenuTarget::Canvas(self.screen_space_to_grid_space(self.mouse_pos))
+        }
     }
 
     fn begin_canvas_interaction(&mut self) {
@@ -920,21 +1481,25 @@ impl Context {
             return;
         }
 
-        if self.alt_mouse_clicked {
+        if self.minimap_hovered && self.left_mouse_clicked {
+            self.click_interaction_type = ClickInteractionType::MiniMap;
+            self.minimap_pan_to(self.mouse_pos);
+        } else if self.alt_mouse_clicked {
             self.click_interaction_type = ClickInteractionType::Panning;
-        } else {
+        } else if self.interaction_mode != InteractionMode::ViewOnly {
             self.click_interaction_type = ClickInteractionType::BoxSelection;
             self.click_interaction_state.box_selection.min = self.mouse_pos;
         }
     }
 
     fn translate_selected_nodes(&mut self) {
-        if self.left_mouse_dragging {
+        if self.left_mouse_dragging && self.interaction_mode == InteractionMode::Edit {
             let delta = self.mouse_delta;
             for idx in self.selected_node_indices.iter() {
                 let node = &mut self.nodes.pool[*idx];
-                if node.draggable {
+                if node.draggable && delta != egui::Vec2::ZERO {
                     node.origin += delta;
+                    self.click_interaction_state.nodes_moved = true;
                 }
             }
         }
@@ -942,7 +1507,7 @@ impl Context {
 
     fn should_link_snap_to_pin(
         &self,
-        start_pin: &PinData,
+        start_pin: &PinData<T>,
         hovered_pin_idx: usize,
         duplicate_link: Option<usize>,
     ) -> bool {
@@ -951,7 +1516,7 @@ impl Context {
             return false;
         }
 
-        if start_pin.kind == end_pin.kind {
+        if !start_pin.kind.can_link(end_pin.kind) {
             return false;
         }
 
@@ -961,6 +1526,45 @@ impl Context {
         true
     }
 
+    /// Directs a link between two pins from the output node to the input node
+    fn link_node_direction(&self, start_pin_idx: usize, end_pin_idx: usize) -> (usize, usize) {
+        let start_pin = &self.pins.pool[start_pin_idx];
+        let end_pin = &self.pins.pool[end_pin_idx];
+        if start_pin.kind.is_link_reversed(end_pin.kind) {
+            (end_pin.parent_node_idx, start_pin.parent_node_idx)
+        } else {
+            (start_pin.parent_node_idx, end_pin.parent_node_idx)
+        }
+    }
+
+    /// Checks if a link between the two pins would close a cycle through the current links
+    fn link_creates_cycle(&self, start_pin_idx: usize, end_pin_idx: usize) -> bool {
+        let (from, to) = self.link_node_direction(start_pin_idx, end_pin_idx);
+        let mut adjacency: HashMap<usize, Vec<usize>> = HashMap::new();
+        for (idx, link) in self.links.pool.iter().enumerate() {
+            if !self.links.in_use[idx] || self.deleted_link_idx == Some(idx) {
+                continue;
+            }
+            let (link_from, link_to) =
+                self.link_node_direction(link.start_pin_index, link.end_pin_index);
+            adjacency.entry(link_from).or_default().push(link_to);
+        }
+
+        let mut visited = HashSet::new();
+        let mut stack = vec![to];
+        while let Some(node_idx) = stack.pop() {
+            if node_idx == from {
+                return true;
+            }
+            if visited.insert(node_idx) {
+                if let Some(next) = adjacency.get(&node_idx) {
+                    stack.extend(next.iter().copied());
+                }
+            }
+        }
+        false
+    }
+
     fn box_selector_update_selection(&mut self) -> egui::Rect {
         let mut box_rect = self.click_interaction_state.box_selection;
         if box_rect.min.x > box_rect.max.x {
@@ -971,70 +1575,28 @@ impl Context {
             std::mem::swap(&mut box_rect.min.y, &mut box_rect.max.y);
         }
 
+        let mut candidates = Vec::new();
         self.selected_node_indices.clear();
-        for (idx, node) in self.nodes.pool.iter().enumerate() {
-            if self.nodes.in_use[idx] && box_rect.intersects(node.rect) {
+        self.node_grid.query_rect(box_rect, &mut candidates);
+        for idx in candidates.iter().copied() {
+            if self.nodes.in_use[idx] && box_rect.intersects(self.nodes.pool[idx].rect) {
                 self.selected_node_indices.push(idx);
             }
         }
 
         self.selected_link_indices.clear();
-        for (idx, link) in self.links.pool.iter().enumerate() {
-            if self.links.in_use[idx] {
-                let pin_start = &self.pins.pool[link.start_pin_index];
-                let pin_end = &self.pins.pool[link.end_pin_index];
-                let node_start_rect = self.nodes.pool[pin_start.parent_node_idx].rect;
-                let node_end_rect = self.nodes.pool[pin_end.parent_node_idx].rect;
-                let start = self.style.get_screen_space_pin_coordinates(
-                    &node_start_rect,
-                    &pin_start.attribute_rect,
-                    pin_start.kind,
-                );
-                let end = self.style.get_screen_space_pin_coordinates(
-                    &node_end_rect,
-                    &pin_end.attribute_rect,
-                    pin_end.kind,
-                );
-
-                if self.rectangle_overlaps_link(&box_rect, &start, &end, pin_start.kind) {
-                    self.selected_link_indices.push(idx);
-                }
+        self.link_grid.query_rect(box_rect, &mut candidates);
+        for idx in candidates {
+            if self.links.in_use[idx] && self.rectangle_overlaps_link(&box_rect, idx) {
+                self.selected_link_indices.push(idx);
             }
         }
         box_rect
     }
 
     #[inline]
-    fn rectangle_overlaps_link(
-        &self,
-        rect: &egui::Rect,
-        start: &egui::Pos2,
-        end: &egui::Pos2,
-        start_type: AttributeType,
-    ) -> bool {
-        let mut lrect = egui::Rect::from_min_max(*start, *end);
-        if lrect.min.x > lrect.max.x {
-            std::mem::swap(&mut lrect.min.x, &mut lrect.max.x);
-        }
-
-        if lrect.min.y > lrect.max.y {
-            std::mem::swap(&mut lrect.min.y, &mut lrect.max.y);
-        }
-
-        if rect.intersects(lrect) {
-            if rect.contains(*start) || rect.contains(*end) {
-                return true;
-            }
-
-            let link_data = LinkBezierData::get_link_renderable(
-                *start,
-                *end,
-                start_type,
-                self.style.link_line_segments_per_length,
-            );
-            return link_data.rectangle_overlaps_bezier(rect);
-        }
-        false
+    fn rectangle_overlaps_link(&mut self, rect: &egui::Rect, link_idx: usize) -> bool {
+        self.link_renderable(link_idx).rectangle_overlaps_bezier(rect)
     }
 
     fn click_interaction_update(&mut self, ui: &mut egui::Ui) {
@@ -1068,6 +1630,13 @@ impl Context {
                 self.translate_selected_nodes();
                 if self.left_mouse_released {
                     self.click_interaction_type = ClickInteractionType::None;
+                    if self.resolve_overlaps_on_drop
+                        && self.click_interaction_state.nodes_moved
+                        && self.interaction_mode == InteractionMode::Edit
+                    {
+                        // the dropped nodes stay where the user put them
+                        self.separate_nodes(&self.selected_node_indices.clone());
+                    }
                 }
             }
             ClickInteractionType::Link => {
@@ -1083,11 +1652,20 @@ impl Context {
                     )
                 });
 
-                let should_snap = self.hovered_pin_index.map_or(false, |idx| {
-                    let start_pin =
-                        &self.pins.pool[self.click_interaction_state.link_creation.start_pin_idx];
-                    self.should_link_snap_to_pin(start_pin, idx, maybe_duplicate_link_idx)
-                });
+                let creates_cycle = match self.hovered_pin_index {
+                    Some(idx) if self.acyclic => self.link_creates_cycle(
+                        self.click_interaction_state.link_creation.start_pin_idx,
+                        idx,
+                    ),
+                    _ => false,
+                };
+
+                let should_snap = !creates_cycle
+                    && self.hovered_pin_index.map_or(false, |idx| {
+                        let start_pin = &self.pins.pool
+                            [self.click_interaction_state.link_creation.start_pin_idx];
+                        self.should_link_snap_to_pin(start_pin, idx, maybe_duplicate_link_idx)
+                    });
 
                 let snapping_pin_changed = self
                     .click_interaction_state
@@ -1106,24 +1684,37 @@ impl Context {
                     &self.pins.pool[self.click_interaction_state.link_creation.start_pin_idx];
                 let start_pos = self.get_screen_space_pin_coordinates(start_pin);
 
-                let end_pos = if should_snap {
-                    self.get_screen_space_pin_coordinates(
-                        &self.pins.pool[self.hovered_pin_index.unwrap()],
+                // a link that isn't snapped arrives at the mouse from the opposite direction
+                let (end_pos, end_normal, end_type) = if should_snap {
+                    let end_pin = &self.pins.pool[self.hovered_pin_index.unwrap()];
+                    (
+                        self.get_screen_space_pin_coordinates(end_pin),
+                        end_pin.side.normal(),
+                        end_pin.kind,
                     )
                 } else {
-                    self.mouse_pos
+                    (
+                        self.mouse_pos,
+                        -start_pin.side.normal(),
+                        AttributeType::None,
+                    )
                 };
 
-                let link_data = LinkBezierData::get_link_renderable(
-                    start_pos,
-                    end_pos,
-                    start_pin.kind,
-                    self.style.link_line_segments_per_length,
-                );
-                ui.painter().add(link_data.draw((
-                    self.style.link_thickness,
-                    self.style.colors[ColorStyle::Link as usize],
-                )));
+                let link_data = LinkBezierData::get_link_renderable(LinkRenderableKey {
+                    start: start_pos,
+                    end: end_pos,
+                    start_normal: start_pin.side.normal(),
+                    end_normal,
+                    start_type: start_pin.kind,
+                    end_type,
+                    tolerance: self.style.link_tessellation_tolerance,
+                });
+                let link_color = if creates_cycle {
+                    self.style.colors[ColorStyle::LinkInvalid as usize]
+                } else {
+                    self.style.colors[ColorStyle::Link as usize]
+                };
+                ui.painter().add(link_data.draw((self.style.link_thickness, link_color)));
 
                 let link_creation_on_snap = self.hovered_pin_index.map_or(false, |idx| {
                     (self.pins.pool[idx].flags & AttributeFlags::EnableLinkCreationOnSnap as usize)
@@ -1163,6 +1754,13 @@ impl Context {
                     self.click_interaction_type = ClickInteractionType::None;
                 }
             }
+            ClickInteractionType::MiniMap => {
+                if self.left_mouse_dragging {
+                    self.minimap_pan_to(self.mouse_pos);
+                } else {
+                    self.click_interaction_type = ClickInteractionType::None;
+                }
+            }
             ClickInteractionType::None => (),
         }
     }
@@ -1180,6 +1778,11 @@ impl Context {
     }
 
     fn begin_link_interaction(&mut self, idx: usize) {
+        match self.interaction_mode {
+            InteractionMode::Edit => (),
+            InteractionMode::SelectionOnly => return self.begin_link_selection(idx),
+            InteractionMode::ViewOnly => return,
+        }
         if self.click_interaction_type == ClickInteractionType::LinkCreation {
             if (self.hovered_pin_flags & AttributeFlags::EnableLinkDetachWithDragClick as usize)
                 != 0
@@ -1207,6 +1810,9 @@ impl Context {
     }
 
     fn begin_link_creation(&mut self, hovered_pin_idx: usize) {
+        if self.interaction_mode != InteractionMode::Edit {
+            return;
+        }
         self.click_interaction_type = ClickInteractionType::LinkCreation;
         self.click_interaction_state.link_creation.start_pin_idx = hovered_pin_idx;
         self.click_interaction_state.link_creation.end_pin_index.take();
@@ -1222,13 +1828,10 @@ impl Context {
     }
 
     fn find_duplicate_link(&self, start_pin_idx: usize, end_pin_idx: usize) -> Option<usize> {
-        let mut test_link = LinkData::new(0);
-        test_link.start_pin_index = start_pin_idx;
-        test_link.end_pin_index = end_pin_idx;
         for (idx, (link, in_use)) in
             self.links.pool.iter().zip(self.links.in_use.iter()).enumerate()
         {
-            if *in_use && *link == test_link {
+            if *in_use && link.connects(start_pin_idx, end_pin_idx) {
                 return Some(idx);
             }
         }
@@ -1236,10 +1839,13 @@ impl Context {
     }
 
     fn begin_node_selection(&mut self, idx: usize) {
-        if self.click_interaction_type != ClickInteractionType::None {
+        if self.click_interaction_type != ClickInteractionType::None
+            || self.interaction_mode == InteractionMode::ViewOnly
+        {
             return;
         }
         self.click_interaction_type = ClickInteractionType::Node;
+        self.click_interaction_state.nodes_moved = false;
         if !self.selected_node_indices.contains(&idx) {
             self.selected_node_indices.clear();
             self.selected_link_indices.clear();
@@ -1266,6 +1872,7 @@ enum ClickInteractionType {
     LinkCreation,
     Panning,
     BoxSelection,
+    MiniMap,
     None,
 }
 
@@ -1290,6 +1897,8 @@ struct ClickInteractionState {
     link_creation: ClickInteractionStateLinkCreation,
     #[derivative(Default(value = "[[0.0; 2].into(); 2].into()"))]
     box_selection: egui::Rect,
+    /// Whether the selected nodes have been dragged since the node interaction began
+    nodes_moved: bool,
 }
 
 #[derive(Debug)]
@@ -1316,6 +1925,26 @@ impl StyleElement {
     }
 }
 
+/// The number of elements that were skipped by viewport culling, see Context::set_viewport_culling
+#[derive(Debug, Default, Clone, Copy)]
+pub struct CullingStats {
+    pub nodes: usize,
+    pub links: usize,
+}
+
+/// A problem with the nodes and links submitted in a frame, see Context::diagnostics
+#[derive(Debug, Clone, PartialEq)]
+pub enum Diagnostic<T> {
+    /// More than one node used this id, only the first one was shown
+    DuplicateNode(T),
+    /// More than one attribute used this pin id, only the first one has a pin
+    DuplicatePin(T),
+    /// More than one link used this id, only the first one was shown
+    DuplicateLink(T),
+    /// The link refers to a pin that wasn't submitted this frame, so it wasn't shown
+    DanglingLink { link: T, pin: T },
+}
+
 /// This controls the modifers needed for certain mouse interactions
 #[derive(Derivative, Debug)]
 #[derivative(Default)]
@@ -1333,6 +1962,29 @@ pub struct IO {
     pub alt_mouse_button: Option<egui::PointerButton>,
 }
 
+/// The element a context menu was opened on, see Context::context_menu_target
+#[derive(Debug, Clone, PartialEq)]
+pub enum ContextMenuTarget<T> {
+    /// Empty space in the editor, at this position in grid space
+    Canvas(egui::Pos2),
+    Node(T),
+    Link(T),
+    Pin(T),
+}
+
+/// Controls which interactions are allowed in the editor, see Context::set_interaction_mode
+#[derive(Derivative, Debug, Clone, Copy, PartialEq)]
+#[derivative(Default)]
+pub enum InteractionMode {
+    /// Links can be created and detached and nodes can be dragged
+    #[derivative(Default)]
+    Edit,
+    /// Nodes and links can only be selected
+    SelectionOnly,
+    /// Only panning and hover feedback
+    ViewOnly,
+}
+
 /// Used to track which Egui Modifier needs to be pressed for certain IO actions
 #[derive(Debug)]
 pub enum Modifiers {
@@ -1355,49 +2007,54 @@ impl Modifiers {
     }
 }
 
-trait Id {
-    fn id(&self) -> usize;
-    fn new(id: usize) -> Self;
+trait Id<I> {
+    fn id(&self) -> &I;
+    fn new(id: I) -> Self;
 }
 
-#[derive(Default, Debug)]
-struct ObjectPool<T> {
+#[derive(Derivative)]
+#[derivative(Default(bound = ""), Debug)]
+struct ObjectPool<T, I> {
     pool: Vec<T>,
     in_use: Vec<bool>,
     free: Vec<usize>,
-    map: HashMap<usize, usize>,
+    map: HashMap<I, usize>,
 }
 
-impl<T> ObjectPool<T> {
-    fn find(&self, id: usize) -> Option<usize> {
-        self.map.get(&id).copied()
+impl<T, I: Hash + Eq> ObjectPool<T, I> {
+    fn find(&self, id: &I) -> Option<usize> {
+        self.map.get(id).copied()
     }
     fn reset(&mut self) {
         self.in_use.iter_mut().for_each(|x| *x = false);
     }
 }
 
-impl<T: Id> ObjectPool<T> {
+impl<T: Id<I>, I: Hash + Eq + Clone> ObjectPool<T, I> {
+    fn ids(&self) -> impl Iterator<Item = &I> {
+        self.pool.iter().zip(self.in_use.iter()).filter(|(_, in_use)| **in_use).map(|(x, _)| x.id())
+    }
+
     fn update(&mut self) {
         self.free.clear();
         for (i, (in_use, obj)) in self.in_use.iter().zip(self.pool.iter()).enumerate() {
             if !*in_use {
-                self.map.remove(&obj.id());
+                self.map.remove(obj.id());
                 self.free.push(i);
             }
         }
     }
 
-    fn find_or_create_index(&mut self, id: usize) -> usize {
+    fn find_or_create_index(&mut self, id: I) -> usize {
         let index = {
-            if let Some(index) = self.find(id) {
+            if let Some(index) = self.find(&id) {
                 index
             } else {
                 let index = if let Some(index) = self.free.pop() {
-                    self.pool[index] = T::new(id);
+                    self.pool[index] = T::new(id.clone());
                     index
                 } else {
-                    self.pool.push(T::new(id));
+                    self.pool.push(T::new(id.clone()));
                     self.in_use.push(false);
                     self.pool.len() - 1
                 };
@@ -1410,30 +2067,29 @@ impl<T: Id> ObjectPool<T> {
     }
 }
 
-impl Context {
+impl<T: Hash + Eq + Clone> Context<T> {
     fn node_pool_update(&mut self) {
         self.nodes.free.clear();
         for (i, (in_use, node)) in
             self.nodes.in_use.iter_mut().zip(self.nodes.pool.iter_mut()).enumerate()
         {
-            if *in_use {
-                node.pin_indices.clear();
-            } else {
+            if !*in_use {
                 if self.nodes.map.contains_key(&node.id) {
                     self.node_depth_order.retain(|x| *x != i);
                 }
+                node.pin_indices.clear();
                 self.nodes.map.remove(&node.id);
                 self.nodes.free.push(i);
             }
         }
     }
 
-    fn node_pool_find_or_create_index(&mut self, id: usize, origin: Option<egui::Pos2>) -> usize {
+    fn node_pool_find_or_create_index(&mut self, id: T, origin: Option<egui::Pos2>) -> usize {
         let index = {
-            if let Some(index) = self.nodes.find(id) {
+            if let Some(index) = self.nodes.find(&id) {
                 index
             } else {
-                let mut new_node = NodeData::new(id);
+                let mut new_node = NodeData::new(id.clone());
                 if let Some(origin) = origin {
                     new_node.origin = self.screen_space_to_grid_space(origin);
                 }
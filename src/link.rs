@@ -1,4 +1,5 @@
 use super::*;
+use crate::geometry::{segment_closest_point, segment_overlaps_rect, BezierCurve};
 use derivative::Derivative;
 use egui::epaint::PathShape;
 
@@ -28,44 +29,48 @@ pub struct LinkDataColorStyle {
 }
 #[derive(Derivative)]
 #[derivative(Debug)]
-pub struct LinkData {
-    pub id: usize,
+pub struct LinkData<T> {
+    pub id: T,
     pub start_pin_index: usize,
     pub end_pin_index: usize,
     #[derivative(Debug = "ignore")]
     pub color_style: LinkDataColorStyle,
     #[derivative(Debug = "ignore")]
     pub shape: Option<egui::layers::ShapeIdx>,
+    #[derivative(Debug = "ignore")]
+    pub renderable: Option<LinkBezierData>,
 }
 
-impl Id for LinkData {
-    fn id(&self) -> usize {
-        self.id
+impl<T> Id<T> for LinkData<T> {
+    fn id(&self) -> &T {
+        &self.id
     }
 
-    fn new(id: usize) -> Self {
+    fn new(id: T) -> Self {
         Self {
             id,
             start_pin_index: Default::default(),
             end_pin_index: Default::default(),
             color_style: Default::default(),
             shape: None,
+            renderable: None,
         }
     }
 }
 
-impl Default for LinkData {
+impl<T: Default> Default for LinkData<T> {
     fn default() -> Self {
-        Self::new(0)
+        Self::new(T::default())
     }
 }
 
-impl PartialEq for LinkData {
-    fn eq(&self, rhs: &Self) -> bool {
+impl<T> LinkData<T> {
+    /// Checks if the link connects the two pins, in either direction
+    pub fn connects(&self, start_pin_index: usize, end_pin_index: usize) -> bool {
         let mut lhs_start = self.start_pin_index;
         let mut lhs_end = self.end_pin_index;
-        let mut rhs_start = rhs.start_pin_index;
-        let mut rhs_end = rhs.end_pin_index;
+        let mut rhs_start = start_pin_index;
+        let mut rhs_end = end_pin_index;
 
         if lhs_start > lhs_end {
             std::mem::swap(&mut lhs_start, &mut lhs_end);
@@ -75,176 +80,147 @@ impl PartialEq for LinkData {
             std::mem::swap(&mut rhs_start, &mut rhs_end);
         }
 
-        lhs_start == rhs_start && lhs_end == rhs_start
+        lhs_start == rhs_start && lhs_end == rhs_end
     }
 }
 
-#[derive(Debug)]
-pub struct BezierCurve(egui::Pos2, egui::Pos2, egui::Pos2, egui::Pos2);
-
-impl BezierCurve {
-    #[inline]
-    pub fn eval(&self, t: f32) -> egui::Pos2 {
-        <[f32; 2]>::from(
-            (1.0 - t).powi(3) * self.0.to_vec2()
-                + 3.0 * (1.0 - t).powi(2) * t * self.1.to_vec2()
-                + 3.0 * (1.0 - t) * t.powi(2) * self.2.to_vec2()
-                + t.powi(3) * self.3.to_vec2(),
-        )
-        .into()
+impl<T> PartialEq for LinkData<T> {
+    fn eq(&self, rhs: &Self) -> bool {
+        self.connects(rhs.start_pin_index, rhs.end_pin_index)
     }
+}
 
-    #[inline]
-    pub fn get_containing_rect_for_bezier_curve(&self, hover_distance: f32) -> egui::Rect {
-        let min = self.0.min(self.3);
-        let max = self.0.max(self.3);
-
-        let mut rect = egui::Rect::from_min_max(min, max);
-        rect.extend_with(self.1);
-        rect.extend_with(self.2);
-        rect.expand(hover_distance)
-    }
+/// The inputs a flattened link was computed from
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub(crate) struct LinkRenderableKey {
+    pub start: egui::Pos2,
+    pub end: egui::Pos2,
+    pub start_normal: egui::Vec2,
+    pub end_normal: egui::Vec2,
+    pub start_type: AttributeType,
+    pub end_type: AttributeType,
+    pub tolerance: f32,
 }
 
 #[derive(Debug)]
 pub(crate) struct LinkBezierData {
+    pub key: LinkRenderableKey,
     pub bezier: BezierCurve,
-    pub num_segments: usize,
+    /// The smallest rect containing the curve
+    pub rect: egui::Rect,
+    /// The curve flattened into a polyline, shared by drawing and hit testing
+    pub points: Vec<egui::Pos2>,
 }
 
 impl LinkBezierData {
-    #[inline]
-    pub(crate) fn get_link_renderable(
+    /// The curve of a link going from its output pin to its input pin,
+    /// leaving each pin along the normal of the side it is on
+    pub(crate) fn bezier(
         start: egui::Pos2,
         end: egui::Pos2,
+        start_normal: egui::Vec2,
+        end_normal: egui::Vec2,
         start_type: AttributeType,
-        line_segments_per_length: f32,
-    ) -> Self {
-        let (mut start, mut end) = (start, end);
-        if start_type == AttributeType::Input {
+        end_type: AttributeType,
+    ) -> BezierCurve {
+        let (mut start, mut end) = ((start, start_normal), (end, end_normal));
+        if start_type.is_link_reversed(end_type) {
             std::mem::swap(&mut start, &mut end);
         }
 
-        let link_length = end.distance(start);
-        let offset = egui::vec2(0.25 * link_length, 0.0);
-        Self {
-            bezier: BezierCurve(start, start + offset, end - offset, end),
-            num_segments: 1.max((link_length * line_segments_per_length) as usize),
-        }
+        let offset = 0.25 * end.0.distance(start.0);
+        BezierCurve(
+            start.0,
+            start.0 + start.1 * offset,
+            end.0 + end.1 * offset,
+            end.0,
+        )
     }
 
-    pub(crate) fn get_closest_point_on_cubic_bezier(&self, p: &egui::Pos2) -> egui::Pos2 {
-        let mut p_last = self.bezier.0;
-        let mut p_closest = self.bezier.0;
-        let mut p_closest_dist = f32::MAX;
-        let t_step = 1.0 / self.num_segments as f32;
-        for i in 1..self.num_segments {
-            let p_current = self.bezier.eval(t_step * i as f32);
-            let p_line = line_closest_point(&p_last, &p_current, p);
-            let dist = p.distance_sq(p_line);
-            if dist < p_closest_dist {
-                p_closest = p_line;
-                p_closest_dist = dist;
-            }
-            p_last = p_current;
+    #[inline]
+    pub(crate) fn get_link_renderable(key: LinkRenderableKey) -> Self {
+        let bezier = Self::bezier(
+            key.start,
+            key.end,
+            key.start_normal,
+            key.end_normal,
+            key.start_type,
+            key.end_type,
+        );
+        let mut points = vec![bezier.0];
+        bezier.flatten(key.tolerance, &mut points);
+        Self {
+            key,
+            bezier,
+            rect: bezier.bounding_rect(),
+            points,
         }
-        p_closest
     }
 
-    #[inline]
+    /// The distance to the flattened curve, which is within the tessellation tolerance of the exact distance
     pub(crate) fn get_distance_to_cubic_bezier(&self, pos: &egui::Pos2) -> f32 {
-        let point_on_curve = self.get_closest_point_on_cubic_bezier(pos);
-        pos.distance(point_on_curve)
+        self.points
+            .windows(2)
+            .map(|segment| pos.distance(segment_closest_point(&segment[0], &segment[1], pos)))
+            .fold(pos.distance(self.bezier.0), f32::min)
     }
 
     #[inline]
     pub(crate) fn rectangle_overlaps_bezier(&self, rect: &egui::Rect) -> bool {
-        let mut current = self.bezier.eval(0.0);
-        let dt = 1.0 / self.num_segments as f32;
-        for i in 0..self.num_segments {
-            let next = self.bezier.eval((i + 1) as f32 * dt);
-            if rectangle_overlaps_line_segment(rect, &current, &next) {
-                return true;
-            }
-            current = next;
-        }
-        false
+        rect.intersects(self.rect)
+            && self
+                .points
+                .windows(2)
+                .any(|segment| segment_overlaps_rect(rect, &segment[0], &segment[1]))
     }
 
     pub(crate) fn draw(&self, stroke: impl Into<egui::Stroke>) -> egui::Shape {
-        let points = std::iter::once(self.bezier.0)
-            .chain(
-                (1..self.num_segments)
-                    .map(|x| self.bezier.eval(x as f32 / self.num_segments as f32)),
-            )
-            .chain(std::iter::once(self.bezier.3))
-            .collect();
-        let path_shape = PathShape{
-            points,
+        let path_shape = PathShape {
+            points: self.points.clone(),
             closed: false,
             fill: egui::Color32::TRANSPARENT,
-            stroke: stroke.into()
+            stroke: stroke.into(),
         };
         egui::Shape::Path(path_shape)
     }
 }
 
-#[inline]
-pub fn line_closest_point(a: &egui::Pos2, b: &egui::Pos2, p: &egui::Pos2) -> egui::Pos2 {
-    let ap = *p - *a;
-    let ab_dir = *b - *a;
-    let dot = ap.x * ab_dir.x + ap.y * ab_dir.y;
-    if dot < 0.0 {
-        return *a;
-    }
-    let ab_len_sqr = ab_dir.x * ab_dir.x + ab_dir.y * ab_dir.y;
-    if dot > ab_len_sqr {
-        return *b;
-    }
-    *a + ab_dir * dot / ab_len_sqr
-}
-
-#[inline]
-fn eval_inplicit_line_eq(p1: &egui::Pos2, p2: &egui::Pos2, p: &egui::Pos2) -> f32 {
-    (p2.y * p1.y) * p.x + (p1.x * p2.x) * p.y * (p2.x * p1.y - p1.x * p2.y)
-}
-
-#[inline]
-fn rectangle_overlaps_line_segment(rect: &egui::Rect, p1: &egui::Pos2, p2: &egui::Pos2) -> bool {
-    if rect.contains(*p1) || rect.contains(*p2) {
-        return true;
-    }
-
-    let mut flip_rect = *rect;
-    if flip_rect.min.x > flip_rect.max.x {
-        std::mem::swap(&mut flip_rect.min.x, &mut flip_rect.max.x);
-    }
-
-    if flip_rect.min.y > flip_rect.max.y {
-        std::mem::swap(&mut flip_rect.min.y, &mut flip_rect.max.y);
-    }
-
-    if (p1.x < flip_rect.min.x && p2.x < flip_rect.min.x)
-        || (p1.x > flip_rect.max.x && p2.x > flip_rect.max.x)
-        || (p1.y < flip_rect.min.y && p2.y < flip_rect.min.y)
-        || (p1.y > flip_rect.max.y && p2.y > flip_rect.max.y)
-    {
-        return false;
-    }
-
-    let corner_signs = [
-        eval_inplicit_line_eq(p1, p2, &flip_rect.left_bottom()).signum(),
-        eval_inplicit_line_eq(p1, p2, &flip_rect.left_top()).signum(),
-        eval_inplicit_line_eq(p1, p2, &flip_rect.right_bottom()).signum(),
-        eval_inplicit_line_eq(p1, p2, &flip_rect.right_top()).signum(),
-    ];
-
-    let mut sum = 0.0;
-    let mut sum_abs = 0.0;
-    for sign in corner_signs.iter() {
-        sum += sign;
-        sum_abs += sign.abs();
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn pos() -> impl Strategy<Value = egui::Pos2> {
+        (-200.0f32..200.0, -200.0f32..200.0).prop_map(|(x, y)| egui::pos2(x, y))
+    }
+
+    proptest! {
+        #[test]
+        fn hit_testing_the_polyline_is_within_tolerance_of_the_curve(
+            start in pos(),
+            end in pos(),
+            p in pos(),
+            tolerance in 0.1f32..2.0,
+        ) {
+            let link = LinkBezierData::get_link_renderable(LinkRenderableKey {
+                start,
+                end,
+                start_normal: egui::vec2(1.0, 0.0),
+                end_normal: egui::vec2(-1.0, 0.0),
+                start_type: AttributeType::Output,
+                end_type: AttributeType::Input,
+                tolerance,
+            });
+            let exact = link.bezier.distance(&p);
+            let distance = link.get_distance_to_cubic_bezier(&p);
+            prop_assert!((distance - exact).abs() <= tolerance + 1e-2);
+
+            // a rect around p reaching the curve overlaps the polyline once it reaches past the tolerance
+            let reach = |x: f32| egui::Rect::from_center_size(p, egui::Vec2::splat(2.0 * x));
+            prop_assert!(link.rectangle_overlaps_bezier(&reach(exact + tolerance + 1e-2)));
+            if exact > 1.5 * tolerance {
+                prop_assert!(!link.rectangle_overlaps_bezier(&reach((exact - tolerance) / 2.0)));
+            }
+        }
     }
-
-    (sum.abs() - sum_abs).abs() < f32::EPSILON
 }
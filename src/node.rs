@@ -53,8 +53,8 @@ pub struct NodeDataLayoutStyle {
 
 #[derive(Derivative)]
 #[derivative(Debug)]
-pub(crate) struct NodeData {
-    pub id: usize,
+pub(crate) struct NodeData<T> {
+    pub id: T,
     pub origin: egui::Pos2,
     pub size: egui::Vec2,
     pub title_bar_content_rect: egui::Rect,
@@ -64,6 +64,7 @@ pub(crate) struct NodeData {
     pub layout_style: NodeDataLayoutStyle,
     pub pin_indices: Vec<usize>,
     pub draggable: bool,
+    pub culled: bool,
     #[derivative(Debug = "ignore")]
     pub titlebar_shape: Option<egui::layers::ShapeIdx>,
     #[derivative(Debug = "ignore")]
@@ -72,8 +73,8 @@ pub(crate) struct NodeData {
     pub outline_shape: Option<egui::layers::ShapeIdx>,
 }
 
-impl NodeData {
-    pub fn new(id: usize) -> Self {
+impl<T> NodeData<T> {
+    pub fn new(id: T) -> Self {
         Self {
             id,
             origin: [100.0; 2].into(),
@@ -84,6 +85,7 @@ impl NodeData {
             layout_style: Default::default(),
             pin_indices: Default::default(),
             draggable: true,
+            culled: false,
             titlebar_shape: None,
             background_shape: None,
             outline_shape: None,
@@ -98,11 +100,37 @@ impl NodeData {
             expanded_title_rect.min + egui::vec2(self.rect.width(), expanded_title_rect.height()),
         )
     }
+
+    /// The size of the node as last drawn, or its default size if it hasn't been drawn yet
+    #[inline]
+    pub fn layout_size(&self) -> egui::Vec2 {
+        if self.rect.width() > 0.0 && self.rect.height() > 0.0 {
+            self.rect.size()
+        } else {
+            self.size
+        }
+    }
+
+    /// The node rect in grid space
+    #[inline]
+    pub fn layout_rect(&self) -> egui::Rect {
+        egui::Rect::from_min_size(self.origin - self.layout_style.padding, self.layout_size())
+    }
 }
 
-impl Default for NodeData {
+impl<T> Id<T> for NodeData<T> {
+    fn id(&self) -> &T {
+        &self.id
+    }
+
+    fn new(id: T) -> Self {
+        Self::new(id)
+    }
+}
+
+impl<T: Default> Default for NodeData<T> {
     fn default() -> Self {
-        Self::new(0)
+        Self::new(T::default())
     }
 }
 
@@ -110,30 +138,33 @@ impl Default for NodeData {
 /// This is used so that the nodes can be rendered in the context depth order
 #[derive(Derivative, Default)]
 #[derivative(Debug)]
-pub struct NodeConstructor<'a> {
+pub struct NodeConstructor<'a, T = usize> {
     //node: &'a mut NodeData,
-    pub(crate) id: usize,
+    pub(crate) id: T,
     #[derivative(Debug = "ignore")]
     pub(crate) title: Option<Box<dyn FnOnce(&mut egui::Ui) -> egui::Response + 'a>>,
     #[derivative(Debug = "ignore")]
     pub(crate) attributes: Vec<(
-        usize,
+        T,
         AttributeType,
-        PinArgs,
+        PinArgs<'a>,
+        Option<(T, usize)>,
         Box<dyn FnOnce(&mut egui::Ui) -> egui::Response + 'a>,
     )>,
     pub(crate) pos: Option<egui::Pos2>,
     pub(crate) args: NodeArgs,
 }
 
-impl<'a, 'b> NodeConstructor<'a> {
+impl<'a, 'b, T> NodeConstructor<'a, T> {
     /// Create a new node to be displayed in a Context.
     /// id should be the same accross frames and should not be the same as any other currently used nodes
-    pub fn new(id: usize, args: NodeArgs) -> Self {
+    pub fn new(id: T, args: NodeArgs) -> Self {
         Self {
             id,
+            title: None,
+            attributes: Vec::new(),
+            pos: None,
             args,
-            ..Default::default()
         }
     }
 
@@ -148,11 +179,11 @@ impl<'a, 'b> NodeConstructor<'a> {
     /// the attribute should return a egui::Response to be checked for interaction
     pub fn with_input_attribute(
         mut self,
-        id: usize,
-        args: PinArgs,
+        id: T,
+        args: PinArgs<'a>,
         attribute: impl FnOnce(&mut egui::Ui) -> egui::Response + 'a,
     ) -> Self {
-        self.attributes.push((id, AttributeType::Input, args, Box::new(attribute)));
+        self.attributes.push((id, AttributeType::Input, args, None, Box::new(attribute)));
         self
     }
     /// Add an output attibute to a node, this attribute can be connected to input attributes of other nodes
@@ -160,11 +191,52 @@ impl<'a, 'b> NodeConstructor<'a> {
     /// the attribute should return a egui::Response to be checked for interaction
     pub fn with_output_attribute(
         mut self,
-        id: usize,
-        args: PinArgs,
+        id: T,
+        args: PinArgs<'a>,
+        attribute: impl FnOnce(&mut egui::Ui) -> egui::Response + 'a,
+    ) -> Self {
+        self.attributes.push((id, AttributeType::Output, args, None, Box::new(attribute)));
+        self
+    }
+    /// Add an inout attibute to a node, this attribute can be connected to input, output and inout attributes of other nodes
+    /// id should be the same accross frames and should not be the same as any other currently used attributes
+    /// the attribute should return a egui::Response to be checked for interaction
+    pub fn with_inout_attribute(
+        mut self,
+        id: T,
+        args: PinArgs<'a>,
         attribute: impl FnOnce(&mut egui::Ui) -> egui::Response + 'a,
     ) -> Self {
-        self.attributes.push((id, AttributeType::Output, args, Box::new(attribute)));
+        self.attributes.push((id, AttributeType::InOut, args, None, Box::new(attribute)));
+        self
+    }
+    /// Add a list of attributes of kind that always ends in a free slot, for nodes that take any number of links.
+    /// slots is the number of linked slots and slot slots is the free one, the pin of slot i has the id id.slot_id(i).
+    /// NodeEditorEvent::LinkCreated reports the slot of the pins it links, so the app can add a slot when the free one
+    /// is linked, and NodeEditorEvent::VariadicSlotRemoved reports a slot that lost its link, after which the app
+    /// shows one slot less and the later slots move down one.
+    pub fn with_variadic_attribute(
+        mut self,
+        kind: PinKind,
+        id: T,
+        slots: usize,
+        args: impl Fn(usize) -> PinArgs<'a>,
+        attribute: impl Fn(&mut egui::Ui, usize) -> egui::Response + 'a,
+    ) -> Self
+    where
+        T: SlotId + Clone,
+    {
+        let attribute = std::rc::Rc::new(attribute);
+        for slot in 0..=slots {
+            let attribute = attribute.clone();
+            self.attributes.push((
+                id.slot_id(slot),
+                kind.into(),
+                args(slot),
+                Some((id.clone(), slot)),
+                Box::new(move |ui| attribute(ui, slot)),
+            ));
+        }
         self
     }
     /// Add a static attibute to a node, this attribute can't be connected to any other attributes
@@ -172,13 +244,14 @@ impl<'a, 'b> NodeConstructor<'a> {
     /// the attribute should return a egui::Response to be checked for interaction
     pub fn with_static_attribute(
         mut self,
-        id: usize,
+        id: T,
         attribute: impl FnOnce(&mut egui::Ui) -> egui::Response + 'a,
     ) -> Self {
         self.attributes.push((
             id,
             AttributeType::None,
             PinArgs::default(),
+            None,
             Box::new(attribute),
         ));
         self
@@ -190,7 +263,66 @@ impl<'a, 'b> NodeConstructor<'a> {
         self
     }
     /// Get the id of this NodeConstructor
-    pub fn id(&self) -> usize {
-        self.id
+    pub fn id(&self) -> &T {
+        &self.id
+    }
+}
+
+/// Ids that the pins of a variadic attribute can be derived from, see NodeConstructor::with_variadic_attribute.
+/// A Context with variadic attributes can use usize, egui::Id or (A, usize) pairs as its ids
+pub trait SlotId {
+    /// The id of the pin of slot in the variadic attribute with this id
+    fn slot_id(&self, slot: usize) -> Self;
+}
+
+/// Slot pins mix the attribute id and the slot with splitmix64, see mix_slot.
+/// The ids are the same on every platform with the same pointer width and across releases, so links
+/// saved with them can be loaded again. They are spread over the whole range of usize, so they rarely
+/// meet the ids an app hands out itself, but nothing stops them from colliding with one
+impl SlotId for usize {
+    fn slot_id(&self, slot: usize) -> Self {
+        mix_slot(*self as u64, slot as u64) as usize
+    }
+}
+
+/// Mixes an attribute id and a slot into a slot pin id: splitmix64(splitmix64(id) ^ slot)
+fn mix_slot(id: u64, slot: u64) -> u64 {
+    fn splitmix64(x: u64) -> u64 {
+        let x = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        let x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        x ^ (x >> 31)
+    }
+    splitmix64(splitmix64(id) ^ slot)
+}
+
+/// Slot pins are scoped to the attribute like other egui ids
+impl SlotId for egui::Id {
+    fn slot_id(&self, slot: usize) -> Self {
+        self.with(slot)
+    }
+}
+
+/// Slot pins keep the first part of the attribute id and mix the slot into the second like usize ids,
+/// so the pins of (a, k) are (a, k.slot_id(slot)). Variadic attributes that share a and ordinary pins (a, n)
+/// only clash with them if the mixed ids happen to collide
+impl<A: Clone> SlotId for (A, usize) {
+    fn slot_id(&self, slot: usize) -> Self {
+        (self.0.clone(), self.1.slot_id(slot))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn usize_slot_ids_do_not_change() {
+        // links saved with these ids have to load again after an upgrade
+        assert_eq!(mix_slot(0, 0), 0xa706_dd2f_4d19_7e6f);
+        assert_eq!(mix_slot(7, 0), 0xb8b4_c297_7eab_ce45);
+        assert_eq!(mix_slot(7, 1), 0x3d41_bf49_5cd3_075f);
+        assert_ne!(7usize.slot_id(0), 7usize.slot_id(1));
+        assert_ne!(7usize.slot_id(0), 8usize.slot_id(0));
     }
 }
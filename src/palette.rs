@@ -0,0 +1,369 @@
+use super::*;
+use derivative::Derivative;
+
+/// The height of the list of entries before it scrolls
+const PALETTE_LIST_HEIGHT: f32 = 200.0;
+
+/// A kind of node that can be added from a NodePalette
+#[derive(Debug, Clone)]
+pub struct PaletteEntry<K> {
+    /// Identifies the entry to the app
+    pub key: K,
+    pub name: String,
+    pub category: String,
+    /// The port types of the input pins of the node, matched against PinArgs::port_type
+    pub inputs: Vec<String>,
+    /// The port types of the output pins of the node, matched against PinArgs::port_type
+    pub outputs: Vec<String>,
+}
+
+impl<K> PaletteEntry<K> {
+    pub fn new(key: K, name: impl Into<String>, category: impl Into<String>) -> Self {
+        Self {
+            key,
+            name: name.into(),
+            category: category.into(),
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+        }
+    }
+
+    pub fn with_input(mut self, port_type: impl Into<String>) -> Self {
+        self.inputs.push(port_type.into());
+        self
+    }
+
+    pub fn with_output(mut self, port_type: impl Into<String>) -> Self {
+        self.outputs.push(port_type.into());
+        self
+    }
+
+    /// The first port that can be linked to a pin of kind with port_type, pins without a port type fit any port.
+    /// InOut pins link to either kind of port, inputs are tried first
+    fn compatible_port(&self, kind: AttributeType, port_type: Option<&str>) -> Option<PortRef> {
+        let fits = |ports: &[String]| {
+            ports.iter().position(|x| port_type.is_none() || port_type == Some(x.as_str()))
+        };
+        let input = || fits(&self.inputs).map(PortRef::Input);
+        let output = || fits(&self.outputs).map(PortRef::Output);
+        match kind {
+            AttributeType::Output => input(),
+            AttributeType::Input => output(),
+            AttributeType::InOut => input().or_else(output),
+            AttributeType::None => None,
+        }
+    }
+}
+
+/// A port of a PaletteEntry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PortRef {
+    /// An index into PaletteEntry::inputs
+    Input(usize),
+    /// An index into PaletteEntry::outputs
+    Output(usize),
+}
+
+/// The entry chosen from a NodePalette
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaletteSelection<K, T> {
+    pub entry: K,
+    /// The pin of the dropped link that opened the palette
+    pub pin: Option<T>,
+    /// The port of the new node to link to pin
+    pub port: Option<PortRef>,
+    /// Where the palette was opened in grid space
+    pub pos: egui::Pos2,
+}
+
+#[derive(Debug)]
+struct PaletteState<T> {
+    filter: String,
+    highlighted: usize,
+    pin: Option<T>,
+    /// The kind and port type of pin
+    pin_kind: AttributeType,
+    pin_port_type: Option<String>,
+    screen_pos: egui::Pos2,
+    grid_pos: egui::Pos2,
+    request_focus: bool,
+}
+
+/// A searchable popup of the kinds of nodes the app can add.
+/// It opens when Space or Tab is pressed over the editor, or when a link is dropped on empty canvas,
+/// in which case only entries with a port that fits the pin of the link are listed.
+/// Type to filter the entries, use the arrow keys and Enter to choose one and Escape to close it
+/// ``` rust
+/// # use egui_nodes::{Context, NodeConstructor, NodePalette, PaletteEntry, LinkArgs};
+/// fn editor(ctx: &mut Context, palette: &mut NodePalette<&'static str>, ui: &mut egui::Ui) {
+///     let nodes = Vec::<NodeConstructor>::new();
+///     ctx.show(nodes, Vec::<(usize, usize, usize, LinkArgs)>::new(), ui);
+///     palette.show(ctx, ui);
+///     if let Some(selection) = palette.take_selection() {
+///         println!("add a {} node at {:?}", selection.entry, selection.pos);
+///     }
+/// }
+/// let palette: NodePalette<&str> = NodePalette::new(vec![
+///     PaletteEntry::new("add", "Add", "Math").with_input("f32").with_input("f32").with_output("f32"),
+///     PaletteEntry::new("print", "Print", "Debug").with_input("f32"),
+/// ]);
+/// ```
+#[derive(Derivative)]
+#[derivative(Debug)]
+pub struct NodePalette<K, T = usize> {
+    pub entries: Vec<PaletteEntry<K>>,
+    state: Option<PaletteState<T>>,
+    selection: Option<PaletteSelection<K, T>>,
+}
+
+impl<K: Clone, T: Hash + Eq + Clone> NodePalette<K, T> {
+    pub fn new(entries: Vec<PaletteEntry<K>>) -> Self {
+        Self {
+            entries,
+            state: None,
+            selection: None,
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.state.is_some()
+    }
+
+    pub fn close(&mut self) {
+        self.state = None;
+    }
+
+    /// The entry chosen since the last call
+    pub fn take_selection(&mut self) -> Option<PaletteSelection<K, T>> {
+        self.selection.take()
+    }
+
+    /// Opens the palette if it was triggered this frame and shows it if it is open.
+    /// Call after Context::show with the same ui, or use NodeEditor::palette
+    pub fn show(&mut self, ctx: &Context<T>, ui: &mut egui::Ui) {
+        if let Some(pin) = ctx.link_dropped(false) {
+            self.open(ctx, Some(pin));
+        } else if self.state.is_none()
+            && ctx.interaction_mode == InteractionMode::Edit
+            && ctx.mouse_in_canvas
+            && ui.memory().focus().is_none()
+            && (ui.input().key_pressed(egui::Key::Space) || ui.input().key_pressed(egui::Key::Tab))
+        {
+            self.open(ctx, None);
+        }
+
+        let NodePalette {
+            entries,
+            state: palette_state,
+            selection,
+        } = self;
+        let state = match palette_state {
+            Some(state) => state,
+            None => return,
+        };
+        let candidates = candidates(entries, state);
+
+        let mut close = false;
+        let mut chosen = None;
+        let area = egui::Area::new(ui.make_persistent_id("node_palette"))
+            .order(egui::Order::Foreground)
+            .fixed_pos(state.screen_pos)
+            .show(ui.ctx(), |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    let filter = ui.add(
+                        egui::TextEdit::singleline(&mut state.filter).hint_text("Search nodes"),
+                    );
+                    if state.request_focus {
+                        filter.request_focus();
+                        state.request_focus = false;
+                    }
+                    if filter.changed() {
+                        state.highlighted = 0;
+                    }
+
+                    let mut moved = false;
+                    {
+                        let input = ui.input();
+                        if input.key_pressed(egui::Key::ArrowDown) {
+                            state.highlighted =
+                                (state.highlighted + 1).min(candidates.len().saturating_sub(1));
+                            moved = true;
+                        }
+                        if input.key_pressed(egui::Key::ArrowUp) {
+                            state.highlighted = state.highlighted.saturating_sub(1);
+                            moved = true;
+                        }
+                        if input.key_pressed(egui::Key::Enter) {
+                            chosen = candidates.get(state.highlighted).copied();
+                        }
+                        if input.key_pressed(egui::Key::Escape) {
+                            close = true;
+                        }
+                    }
+
+                    egui::ScrollArea::vertical().max_height(PALETTE_LIST_HEIGHT).show(ui, |ui| {
+                        let mut category = None;
+                        for (row, (idx, port)) in candidates.iter().enumerate() {
+                            let entry = &entries[*idx];
+                            let label = if state.filter.is_empty() {
+                                if category != Some(&entry.category) {
+                                    ui.small(&entry.category);
+                                    category = Some(&entry.category);
+                                }
+                                entry.name.clone()
+                            } else {
+                                format!("{} ({})", entry.name, entry.category)
+                            };
+                            let response = ui.selectable_label(row == state.highlighted, label);
+                            if moved && row == state.highlighted {
+                                response.scroll_to_me(egui::Align::Center);
+                            }
+                            if response.clicked() {
+                                chosen = Some((*idx, *port));
+                            }
+                        }
+                        if candidates.is_empty() {
+                            ui.label("No matching nodes");
+                        }
+                    });
+                });
+            });
+
+        let pointer = &ui.input().pointer;
+        if pointer.any_pressed()
+            && !pointer.interact_pos().map(|x| area.response.rect.contains(x)).unwrap_or(true)
+        {
+            close = true;
+        }
+
+        if let Some((idx, port)) = chosen {
+            selection.replace(PaletteSelection {
+                entry: entries[idx].key.clone(),
+                pin: state.pin.clone(),
+                port,
+                pos: state.grid_pos,
+            });
+            close = true;
+        }
+        if close {
+            palette_state.take();
+        }
+    }
+
+    fn open(&mut self, ctx: &Context<T>, pin: Option<T>) {
+        let (pin_kind, pin_port_type) = match pin.as_ref().and_then(|x| ctx.pins.find(x)) {
+            Some(idx) => {
+                let pin = &ctx.pins.pool[idx];
+                (pin.kind, pin.port_type.clone())
+            }
+            None => (AttributeType::None, None),
+        };
+        self.state.replace(PaletteState {
+            filter: String::new(),
+            highlighted: 0,
+            pin,
+            pin_kind,
+            pin_port_type,
+            screen_pos: ctx.mouse_pos,
+            grid_pos: ctx.screen_space_to_grid_space(ctx.mouse_pos),
+            request_focus: true,
+        });
+    }
+}
+
+/// The entries that fit the pin and the filter, with the port that fits the pin.
+/// Sorted by category without a filter and by how well they match otherwise
+fn candidates<K, T>(
+    entries: &[PaletteEntry<K>],
+    state: &PaletteState<T>,
+) -> Vec<(usize, Option<PortRef>)> {
+    let mut candidates = entries
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, entry)| {
+            let port = if state.pin.is_some() {
+                Some(entry.compatible_port(state.pin_kind, state.pin_port_type.as_deref())?)
+            } else {
+                None
+            };
+            let score = if state.filter.is_empty() {
+                0
+            } else {
+                fuzzy_score(&state.filter, &entry.name)
+                    .or_else(|| fuzzy_score(&state.filter, &entry.category).map(|x| x / 2))?
+            };
+            Some((score, idx, port))
+        })
+        .collect::<Vec<_>>();
+    if state.filter.is_empty() {
+        candidates.sort_by(|a, b| entries[a.1].category.cmp(&entries[b.1].category));
+    } else {
+        candidates.sort_by_key(|x| std::cmp::Reverse(x.0));
+    }
+    candidates.into_iter().map(|(_, idx, port)| (idx, port)).collect()
+}
+
+/// How well pattern matches text as a case insensitive subsequence, higher is better.
+/// Matches at the start of words and runs of consecutive matches score more, gaps score less
+fn fuzzy_score(pattern: &str, text: &str) -> Option<i32> {
+    let text = text.chars().flat_map(char::to_lowercase).collect::<Vec<_>>();
+    let mut score = 0;
+    let mut start = 0;
+    let mut last = None;
+    for c in pattern.chars().filter(|x| !x.is_whitespace()).flat_map(char::to_lowercase) {
+        let found = start + text[start..].iter().position(|x| *x == c)?;
+        score += if found == 0 || !text[found - 1].is_alphanumeric() {
+            8
+        } else if last == Some(found - 1) {
+            5
+        } else {
+            1
+        };
+        score -= (found - start) as i32;
+        last = Some(found);
+        start = found + 1;
+    }
+    Some(score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fuzzy_score_matches_subsequences() {
+        assert!(fuzzy_score("mul", "Multiply").is_some());
+        assert!(fuzzy_score("mty", "Multiply").is_some());
+        assert!(fuzzy_score("ADD", "add").is_some());
+        assert!(fuzzy_score("ml", "Lerp").is_none());
+        assert!(fuzzy_score("", "Lerp").is_some());
+    }
+
+    #[test]
+    fn inout_pins_fit_inputs_and_outputs() {
+        let source = PaletteEntry::new((), "Constant", "Input").with_output("f32");
+        let sink = PaletteEntry::new((), "Print", "Debug").with_input("bool").with_input("f32");
+        let f32 = Some("f32");
+        assert_eq!(
+            source.compatible_port(AttributeType::InOut, f32),
+            Some(PortRef::Output(0))
+        );
+        assert_eq!(
+            sink.compatible_port(AttributeType::InOut, f32),
+            Some(PortRef::Input(1))
+        );
+        assert_eq!(source.compatible_port(AttributeType::Output, f32), None);
+        assert_eq!(
+            source.compatible_port(AttributeType::Input, None),
+            Some(PortRef::Output(0))
+        );
+        assert_eq!(sink.compatible_port(AttributeType::None, None), None);
+    }
+
+    #[test]
+    fn fuzzy_score_prefers_prefixes_and_word_starts() {
+        let score = |x| fuzzy_score("mu", x).unwrap();
+        assert!(score("Multiply") > score("Sum Up"));
+        assert!(score("Sum Up") > score("Summum"));
+    }
+}
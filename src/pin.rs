@@ -1,33 +1,77 @@
 use super::*;
 use derivative::Derivative;
 
-#[derive(Default, Debug)]
+pub(crate) type PinTooltip<'a> = Box<dyn FnOnce(&mut egui::Ui) + 'a>;
+type PinShapeFn =
+    std::sync::Arc<dyn Fn(&PinShapeState, &egui::Painter) -> egui::Shape + Send + Sync>;
+
+#[derive(Derivative)]
+#[derivative(Default, Debug)]
 /// The Visual Style of a Link.
 /// If feilds are None then the Context style is used.
-/// shape defualts to CircleFilled
-pub struct PinArgs {
+/// shape defualts to Circle, so pins without links are hollow
+pub struct PinArgs<'a> {
     pub shape: PinShape,
+    /// The shape of the pin while it has links, by default the filled variant of shape
+    pub connected_shape: Option<PinShape>,
     pub flags: Option<usize>,
     pub background: Option<egui::Color32>,
     pub hovered: Option<egui::Color32>,
+    /// The color of the pin while it has links and is not hovered
+    pub connected: Option<egui::Color32>,
+    /// The type of data the pin carries, NodePalette only lists entries with a port of the same type
+    pub port_type: Option<String>,
+    /// Shown next to the pointer while the pin is hovered, only used in the frame it is passed in
+    #[derivative(Debug = "ignore")]
+    pub tooltip: Option<PinTooltip<'a>>,
+    /// Drawn next to the pin outside of the node
+    pub label: Option<String>,
+    /// The edge of the node the pin is on, by default inputs are on the left and all other pins on the right
+    pub side: Option<PinSide>,
+    /// The distance of the pin along its edge from the top left corner of the node,
+    /// by default the pin is placed at the center of its attribute
+    pub side_offset: Option<f32>,
+    /// How close the pointer has to be to hover the pin, by default Style::pin_hover_radius
+    pub hover_radius: Option<f32>,
 }
 
-impl PinArgs {
+impl<'a> PinArgs<'a> {
     pub const fn new() -> Self {
         Self {
-            shape: PinShape::CircleFilled,
+            shape: PinShape::Circle,
+            connected_shape: None,
             flags: None,
             background: None,
             hovered: None,
+            connected: None,
+            port_type: None,
+            tooltip: None,
+            label: None,
+            side: None,
+            side_offset: None,
+            hover_radius: None,
         }
     }
+
+    pub fn with_tooltip(mut self, tooltip: impl FnOnce(&mut egui::Ui) + 'a) -> Self {
+        self.tooltip.replace(Box::new(tooltip));
+        self
+    }
+
+    pub fn with_label(mut self, label: impl Into<String>) -> Self {
+        self.label.replace(label.into());
+        self
+    }
 }
 
+/// The kind of attribute a pin belongs to
 #[derive(PartialEq, Clone, Copy, Debug)]
-pub(crate) enum AttributeType {
+pub enum AttributeType {
     None,
     Input,
     Output,
+    /// Can be linked to inputs, outputs and other InOut pins
+    InOut,
 }
 impl Default for AttributeType {
     fn default() -> Self {
@@ -35,9 +79,66 @@ impl Default for AttributeType {
     }
 }
 
+/// The kinds of attribute that have a pin, see NodeConstructor::with_variadic_attribute
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum PinKind {
+    Input,
+    Output,
+    /// Can be linked to inputs, outputs and other InOut pins
+    InOut,
+}
+
+impl From<PinKind> for AttributeType {
+    fn from(kind: PinKind) -> Self {
+        match kind {
+            PinKind::Input => Self::Input,
+            PinKind::Output => Self::Output,
+            PinKind::InOut => Self::InOut,
+        }
+    }
+}
+
+impl AttributeType {
+    /// Can pins of the two kinds be linked
+    pub(crate) fn can_link(self, other: Self) -> bool {
+        match (self, other) {
+            (Self::None, _) | (_, Self::None) => false,
+            (Self::InOut, _) | (_, Self::InOut) => true,
+            (start, end) => start != end,
+        }
+    }
+
+    /// Does a link dragged from a pin of this kind to a pin of kind end go from end to the start pin.
+    /// Links go from outputs to inputs, links between two InOut pins go the way they were dragged
+    pub(crate) fn is_link_reversed(self, end: Self) -> bool {
+        self == Self::Input || end == Self::Output
+    }
+}
+
+/// The edge of a node a pin is placed on
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PinSide {
+    Left,
+    Right,
+    Top,
+    Bottom,
+}
+
+impl PinSide {
+    /// The direction links leave the pin in
+    pub fn normal(self) -> egui::Vec2 {
+        match self {
+            Self::Left => egui::vec2(-1.0, 0.0),
+            Self::Right => egui::vec2(1.0, 0.0),
+            Self::Top => egui::vec2(0.0, -1.0),
+            Self::Bottom => egui::vec2(0.0, 1.0),
+        }
+    }
+}
+
 /// Controls the shape of an attribut pin.
-/// Triangle and TriangleFilled are not currently implemented and will not be drawn
-#[derive(Clone, Copy, Debug)]
+#[derive(Derivative, Clone)]
+#[derivative(Debug)]
 pub enum PinShape {
     Circle,
     CircleFilled,
@@ -45,13 +146,45 @@ pub enum PinShape {
     TriangleFilled,
     Quad,
     QuadFilled,
+    /// Returns the shape of the pin, the painter can be used to lay out text
+    Custom(#[derivative(Debug = "ignore")] PinShapeFn),
 }
 impl Default for PinShape {
     fn default() -> Self {
-        Self::CircleFilled
+        Self::Circle
     }
 }
 
+impl PinShape {
+    pub fn custom(
+        shape: impl Fn(&PinShapeState, &egui::Painter) -> egui::Shape + Send + Sync + 'static,
+    ) -> Self {
+        Self::Custom(std::sync::Arc::new(shape))
+    }
+
+    /// The filled variant of an outline shape, which connected pins use by default
+    pub(crate) fn filled(&self) -> Self {
+        match self {
+            Self::Circle => Self::CircleFilled,
+            Self::Triangle => Self::TriangleFilled,
+            Self::Quad => Self::QuadFilled,
+            shape => shape.clone(),
+        }
+    }
+}
+
+/// The pin a PinShape::Custom is drawn for
+#[derive(Debug, Clone, Copy)]
+pub struct PinShapeState {
+    pub pos: egui::Pos2,
+    /// The background, connected or hovered color of the pin, hovered takes precedence over connected
+    pub color: egui::Color32,
+    pub hovered: bool,
+    /// Does the pin have any links
+    pub connected: bool,
+    pub side: PinSide,
+}
+
 /// Controls the way that attribute pins behave
 #[derive(Debug)]
 pub enum AttributeFlags {
@@ -69,46 +202,104 @@ pub enum AttributeFlags {
 pub(crate) struct PinDataColorStyle {
     pub background: egui::Color32,
     pub hovered: egui::Color32,
+    pub connected: egui::Color32,
 }
 
 #[derive(Derivative)]
 #[derivative(Debug)]
-pub(crate) struct PinData {
-    pub id: usize,
+pub(crate) struct PinData<T> {
+    pub id: T,
     pub parent_node_idx: usize,
     pub attribute_rect: egui::Rect,
     pub kind: AttributeType,
+    pub side: PinSide,
+    pub side_offset: Option<f32>,
+    /// The id of the variadic attribute of the pin and its slot
+    pub slot: Option<(T, usize)>,
+    pub hover_radius: f32,
+    /// The number of links of the pin in this frame
+    pub link_count: usize,
     pub shape: PinShape,
+    pub connected_shape: Option<PinShape>,
     pub pos: egui::Pos2,
     pub flags: usize,
+    pub port_type: Option<String>,
+    pub label: Option<String>,
     #[derivative(Debug = "ignore")]
     pub color_style: PinDataColorStyle,
     #[derivative(Debug = "ignore")]
     pub shape_gui: Option<egui::layers::ShapeIdx>,
 }
 
-impl Id for PinData {
-    fn id(&self) -> usize {
-        self.id
+impl<T> Id<T> for PinData<T> {
+    fn id(&self) -> &T {
+        &self.id
     }
 
-    fn new(id: usize) -> Self {
+    fn new(id: T) -> Self {
         Self {
             id,
             parent_node_idx: Default::default(),
             attribute_rect: [[0.0; 2].into(); 2].into(),
             kind: AttributeType::None,
+            side: PinSide::Right,
+            side_offset: None,
+            slot: None,
+            hover_radius: 0.0,
+            link_count: 0,
             shape: PinShape::CircleFilled,
+            connected_shape: None,
             pos: Default::default(),
             flags: AttributeFlags::None as usize,
+            port_type: None,
+            label: None,
             color_style: Default::default(),
             shape_gui: None,
         }
     }
 }
 
-impl Default for PinData {
+impl<T: Default> Default for PinData<T> {
     fn default() -> Self {
-        Self::new(0)
+        Self::new(T::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pins_are_hollow_until_linked() {
+        let args = PinArgs::default();
+        assert!(matches!(args.shape, PinShape::Circle));
+        assert!(matches!(PinArgs::new().shape, PinShape::Circle));
+        assert!(matches!(args.shape.filled(), PinShape::CircleFilled));
+        assert!(matches!(PinShape::Quad.filled(), PinShape::QuadFilled));
+        assert!(matches!(
+            PinShape::Triangle.filled(),
+            PinShape::TriangleFilled
+        ));
+        assert!(matches!(
+            PinShape::QuadFilled.filled(),
+            PinShape::QuadFilled
+        ));
+    }
+
+    #[test]
+    fn linked_pins_have_their_own_color() {
+        let rgb = |x: egui::Color32| [x.r(), x.g(), x.b()];
+        for colors in [
+            ColorStyle::colors_dark(),
+            ColorStyle::colors_classic(),
+            ColorStyle::colors_light(),
+        ]
+        .iter()
+        {
+            assert_ne!(
+                rgb(colors[ColorStyle::Pin as usize]),
+                rgb(colors[ColorStyle::PinConnected as usize])
+            );
+        }
     }
 }
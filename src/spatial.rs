@@ -0,0 +1,159 @@
+use super::*;
+
+/// Rects covering more cells than this are kept in a separate list that every query returns
+const MAX_CELLS_PER_ITEM: i64 = 1024;
+
+/// A uniform grid over screen space used to find the elements near a point or rect
+/// without testing every element. Rebuilt every frame.
+#[derive(Debug)]
+pub(crate) struct SpatialGrid {
+    cell_size: f32,
+    cells: HashMap<(i32, i32), Vec<usize>>,
+    oversized: Vec<usize>,
+}
+
+impl SpatialGrid {
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size,
+            cells: HashMap::new(),
+            oversized: Vec::new(),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.clear();
+        self.oversized.clear();
+    }
+
+    pub fn insert(&mut self, idx: usize, rect: egui::Rect) {
+        let (min, max) = self.cell_range(&rect);
+        if !rect.is_finite() || Self::num_cells(min, max) > MAX_CELLS_PER_ITEM {
+            self.oversized.push(idx);
+            return;
+        }
+        for x in min.0..=max.0 {
+            for y in min.1..=max.1 {
+                self.cells.entry((x, y)).or_default().push(idx);
+            }
+        }
+    }
+
+    /// Indices of the elements whose rect might contain pos, in ascending order
+    pub fn query_point(&self, pos: egui::Pos2, out: &mut Vec<usize>) {
+        self.query_rect(egui::Rect::from_min_max(pos, pos), out);
+    }
+
+    /// Indices of the elements whose rect might overlap rect, in ascending order
+    pub fn query_rect(&self, rect: egui::Rect, out: &mut Vec<usize>) {
+        out.clear();
+        out.extend_from_slice(&self.oversized);
+        let (min, max) = self.cell_range(&rect);
+        if Self::num_cells(min, max) > self.cells.len() as i64 {
+            // cheaper to visit the occupied cells than every cell of a huge rect
+            for ((x, y), cell) in self.cells.iter() {
+                if (min.0..=max.0).contains(x) && (min.1..=max.1).contains(y) {
+                    out.extend_from_slice(cell);
+                }
+            }
+        } else {
+            for x in min.0..=max.0 {
+                for y in min.1..=max.1 {
+                    if let Some(cell) = self.cells.get(&(x, y)) {
+                        out.extend_from_slice(cell);
+                    }
+                }
+            }
+        }
+        out.sort_unstable();
+        out.dedup();
+    }
+
+    fn num_cells(min: (i32, i32), max: (i32, i32)) -> i64 {
+        (max.0 as i64 - min.0 as i64 + 1) * (max.1 as i64 - min.1 as i64 + 1)
+    }
+
+    fn cell_range(&self, rect: &egui::Rect) -> ((i32, i32), (i32, i32)) {
+        let cell = |v: f32| (v / self.cell_size).floor() as i32;
+        (
+            (cell(rect.min.x), cell(rect.min.y)),
+            (cell(rect.max.x), cell(rect.max.y)),
+        )
+    }
+}
+
+impl Default for SpatialGrid {
+    fn default() -> Self {
+        Self::new(128.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(min: [f32; 2], max: [f32; 2]) -> egui::Rect {
+        egui::Rect::from_min_max(min.into(), max.into())
+    }
+
+    fn query(grid: &SpatialGrid, rect: egui::Rect) -> Vec<usize> {
+        let mut out = Vec::new();
+        grid.query_rect(rect, &mut out);
+        out
+    }
+
+    #[test]
+    fn rects_are_found_in_every_cell_they_cover() {
+        let mut grid = SpatialGrid::new(10.0);
+        grid.insert(0, rect([1.0, 1.0], [4.0, 4.0]));
+        // covers cells (1, 0) to (3, 2)
+        grid.insert(1, rect([15.0, 5.0], [35.0, 25.0]));
+
+        let mut out = Vec::new();
+        grid.query_point(egui::pos2(2.0, 2.0), &mut out);
+        assert_eq!(out, vec![0]);
+        grid.query_point(egui::pos2(34.0, 24.0), &mut out);
+        assert_eq!(out, vec![1]);
+        grid.query_point(egui::pos2(50.0, 5.0), &mut out);
+        assert!(out.is_empty());
+
+        // an element in several of the queried cells is only returned once
+        assert_eq!(query(&grid, rect([0.0, 0.0], [40.0, 40.0])), vec![0, 1]);
+        assert_eq!(query(&grid, rect([12.0, 12.0], [38.0, 14.0])), vec![1]);
+
+        grid.clear();
+        assert!(query(&grid, rect([0.0, 0.0], [40.0, 40.0])).is_empty());
+    }
+
+    #[test]
+    fn negative_coordinates_use_their_own_cells() {
+        let mut grid = SpatialGrid::new(10.0);
+        grid.insert(0, rect([-15.0, -15.0], [-12.0, -12.0]));
+        grid.insert(1, rect([-5.0, -5.0], [5.0, 5.0]));
+        grid.insert(2, rect([2.0, 2.0], [8.0, 8.0]));
+
+        let mut out = Vec::new();
+        grid.query_point(egui::pos2(-13.0, -13.0), &mut out);
+        assert_eq!(out, vec![0]);
+        // -0.5 is in cell -1, not in cell 0 with the element at 2.0
+        grid.query_point(egui::pos2(-0.5, -0.5), &mut out);
+        assert_eq!(out, vec![1]);
+        grid.query_point(egui::pos2(0.5, 0.5), &mut out);
+        assert_eq!(out, vec![1, 2]);
+        assert_eq!(query(&grid, rect([-20.0, -20.0], [-1.0, -1.0])), vec![0, 1]);
+    }
+
+    #[test]
+    fn huge_and_infinite_rects_are_always_returned() {
+        let mut grid = SpatialGrid::new(1.0);
+        grid.insert(0, rect([0.0, 0.0], [1000.0, 1000.0]));
+        grid.insert(1, egui::Rect::EVERYTHING);
+        grid.insert(2, rect([5.0, 5.0], [6.0, 6.0]));
+        assert_eq!(
+            query(&grid, rect([-50.0, -50.0], [-40.0, -40.0])),
+            vec![0, 1]
+        );
+        // a query covering more cells than are occupied visits the occupied cells instead
+        assert_eq!(query(&grid, rect([-1e6, -1e6], [1e6, 1e6])), vec![0, 1, 2]);
+    }
+}
@@ -19,6 +19,15 @@ pub enum ColorStyle {
     BoxSelectorOutline,
     GridBackground,
     GridLine,
+    LinkInvalid,
+    MiniMapBackground,
+    MiniMapOutline,
+    MiniMapNodeBackground,
+    MiniMapNodeBackgroundSelected,
+    MiniMapLink,
+    MiniMapCanvas,
+    MiniMapCanvasOutline,
+    PinConnected,
     Count,
 }
 
@@ -31,6 +40,8 @@ pub enum StyleVar {
     NodePaddingVertical,
     NodeBorderThickness,
     LinkThickness,
+    /// Has no effect, kept so existing code still compiles
+    #[deprecated(note = "links are flattened adaptively, use StyleVar::LinkTessellationTolerance")]
     LinkLineSegmentsPerLength,
     LinkHoverDistance,
     PinCircleRadius,
@@ -39,6 +50,8 @@ pub enum StyleVar {
     PinLineThickness,
     PinHoverRadius,
     PinOffset,
+    NodeSeparation,
+    LinkTessellationTolerance,
 }
 
 /// Controls some style aspects
@@ -84,6 +97,23 @@ impl ColorStyle {
             egui::Color32::from_rgba_unmultiplied(40, 40, 50, 200);
         colors[ColorStyle::GridLine as usize] =
             egui::Color32::from_rgba_unmultiplied(200, 200, 200, 40);
+        colors[ColorStyle::LinkInvalid as usize] =
+            egui::Color32::from_rgba_unmultiplied(224, 61, 61, 200);
+        colors[ColorStyle::MiniMapBackground as usize] =
+            egui::Color32::from_rgba_unmultiplied(25, 25, 25, 150);
+        colors[ColorStyle::MiniMapOutline as usize] =
+            egui::Color32::from_rgba_unmultiplied(150, 150, 150, 100);
+        colors[ColorStyle::MiniMapNodeBackground as usize] =
+            egui::Color32::from_rgba_unmultiplied(200, 200, 200, 100);
+        colors[ColorStyle::MiniMapNodeBackgroundSelected as usize] =
+            egui::Color32::from_rgba_unmultiplied(200, 200, 200, 255);
+        colors[ColorStyle::MiniMapLink as usize] = colors[ColorStyle::Link as usize];
+        colors[ColorStyle::MiniMapCanvas as usize] =
+            egui::Color32::from_rgba_unmultiplied(200, 200, 200, 25);
+        colors[ColorStyle::MiniMapCanvasOutline as usize] =
+            egui::Color32::from_rgba_unmultiplied(200, 200, 200, 200);
+        colors[ColorStyle::PinConnected as usize] =
+            egui::Color32::from_rgba_unmultiplied(140, 200, 255, 255);
         colors
     }
 
@@ -121,6 +151,23 @@ impl ColorStyle {
             egui::Color32::from_rgba_unmultiplied(40, 40, 50, 200);
         colors[ColorStyle::GridLine as usize] =
             egui::Color32::from_rgba_unmultiplied(200, 200, 200, 40);
+        colors[ColorStyle::LinkInvalid as usize] =
+            egui::Color32::from_rgba_unmultiplied(204, 99, 99, 153);
+        colors[ColorStyle::MiniMapBackground as usize] =
+            egui::Color32::from_rgba_unmultiplied(25, 25, 25, 150);
+        colors[ColorStyle::MiniMapOutline as usize] =
+            egui::Color32::from_rgba_unmultiplied(150, 150, 150, 100);
+        colors[ColorStyle::MiniMapNodeBackground as usize] =
+            egui::Color32::from_rgba_unmultiplied(200, 200, 200, 100);
+        colors[ColorStyle::MiniMapNodeBackgroundSelected as usize] =
+            egui::Color32::from_rgba_unmultiplied(200, 200, 200, 255);
+        colors[ColorStyle::MiniMapLink as usize] = colors[ColorStyle::Link as usize];
+        colors[ColorStyle::MiniMapCanvas as usize] =
+            egui::Color32::from_rgba_unmultiplied(200, 200, 200, 25);
+        colors[ColorStyle::MiniMapCanvasOutline as usize] =
+            egui::Color32::from_rgba_unmultiplied(200, 200, 200, 200);
+        colors[ColorStyle::PinConnected as usize] =
+            egui::Color32::from_rgba_unmultiplied(140, 150, 220, 230);
         colors
     }
 
@@ -158,6 +205,23 @@ impl ColorStyle {
             egui::Color32::from_rgba_unmultiplied(225, 225, 225, 255);
         colors[ColorStyle::GridLine as usize] =
             egui::Color32::from_rgba_unmultiplied(180, 180, 180, 100);
+        colors[ColorStyle::LinkInvalid as usize] =
+            egui::Color32::from_rgba_unmultiplied(250, 66, 66, 242);
+        colors[ColorStyle::MiniMapBackground as usize] =
+            egui::Color32::from_rgba_unmultiplied(200, 200, 200, 150);
+        colors[ColorStyle::MiniMapOutline as usize] =
+            egui::Color32::from_rgba_unmultiplied(100, 100, 100, 100);
+        colors[ColorStyle::MiniMapNodeBackground as usize] =
+            egui::Color32::from_rgba_unmultiplied(100, 100, 100, 100);
+        colors[ColorStyle::MiniMapNodeBackgroundSelected as usize] =
+            egui::Color32::from_rgba_unmultiplied(100, 100, 100, 255);
+        colors[ColorStyle::MiniMapLink as usize] = colors[ColorStyle::Link as usize];
+        colors[ColorStyle::MiniMapCanvas as usize] =
+            egui::Color32::from_rgba_unmultiplied(50, 50, 50, 25);
+        colors[ColorStyle::MiniMapCanvasOutline as usize] =
+            egui::Color32::from_rgba_unmultiplied(50, 50, 50, 200);
+        colors[ColorStyle::PinConnected as usize] =
+            egui::Color32::from_rgba_unmultiplied(25, 95, 190, 255);
         colors
     }
 }
@@ -166,7 +230,7 @@ impl ColorStyle {
 /// Example:
 /// ``` rust
 /// # use egui_nodes::{Context, Style, ColorStyle};
-/// let mut ctx = Context::default();
+/// let mut ctx: Context = Context::default();
 /// let style = Style { colors: ColorStyle::colors_classic(), ..Default::default() };
 /// ctx.style = style;
 /// ```
@@ -177,8 +241,14 @@ pub struct Style {
     pub node_padding_horizontal: f32,
     pub node_padding_vertical: f32,
     pub node_border_thickness: f32,
+    /// The space kept between nodes when overlaps are resolved
+    pub node_separation: f32,
 
     pub link_thickness: f32,
+    /// The largest distance between a link curve and the lines it is drawn with
+    pub link_tessellation_tolerance: f32,
+    /// Has no effect, kept so existing code still compiles
+    #[deprecated(note = "links are flattened adaptively, use Style::link_tessellation_tolerance")]
     pub link_line_segments_per_length: f32,
     pub link_hover_distance: f32,
 
@@ -194,6 +264,7 @@ pub struct Style {
 }
 
 impl Default for Style {
+    #[allow(deprecated)]
     fn default() -> Self {
         Self {
             grid_spacing: 32.0,
@@ -201,7 +272,9 @@ impl Default for Style {
             node_padding_horizontal: 8.0,
             node_padding_vertical: 8.0,
             node_border_thickness: 1.0,
+            node_separation: 8.0,
             link_thickness: 3.0,
+            link_tessellation_tolerance: 0.25,
             link_line_segments_per_length: 0.1,
             link_hover_distance: 10.0,
             pin_circle_radius: 4.0,
@@ -221,24 +294,45 @@ impl Style {
         &self,
         node_rect: &egui::Rect,
         attribute_rect: &egui::Rect,
-        kind: AttributeType,
+        side: PinSide,
+        side_offset: Option<f32>,
     ) -> egui::Pos2 {
-        let x = match kind {
-            AttributeType::Input => node_rect.min.x - self.pin_offset,
-            _ => node_rect.max.x + self.pin_offset,
-        };
-        egui::pos2(x, 0.5 * (attribute_rect.min.y + attribute_rect.max.y))
+        let y = side_offset.map_or(attribute_rect.center().y, |x| node_rect.min.y + x);
+        let x = side_offset.map_or(attribute_rect.center().x, |x| node_rect.min.x + x);
+        match side {
+            PinSide::Left => egui::pos2(node_rect.min.x - self.pin_offset, y),
+            PinSide::Right => egui::pos2(node_rect.max.x + self.pin_offset, y),
+            PinSide::Top => egui::pos2(x, node_rect.min.y - self.pin_offset),
+            PinSide::Bottom => egui::pos2(x, node_rect.max.y + self.pin_offset),
+        }
+    }
+
+    /// The corners of a triangle pin, pointing the way links run through the side
+    fn pin_triangle_points(&self, pin_pos: egui::Pos2, side: PinSide) -> Vec<egui::Pos2> {
+        let sqrt_3 = 3f32.sqrt();
+        let left_offset = -0.166_666_7 * sqrt_3 * self.pin_triangle_side_length;
+        let right_offset = 0.333_333_3 * sqrt_3 * self.pin_triangle_side_length;
+        let verticacl_offset = 0.5 * self.pin_triangle_side_length;
+        // links run left to right and top to bottom
+        let normal = side.normal();
+        let forward = egui::vec2(normal.x.abs(), normal.y.abs());
+        let across = egui::vec2(-forward.y, forward.x);
+        vec![
+            pin_pos + forward * left_offset + across * verticacl_offset,
+            pin_pos + forward * right_offset,
+            pin_pos + forward * left_offset - across * verticacl_offset,
+        ]
     }
 
     pub(crate) fn draw_pin_shape(
         &self,
-        pin_pos: egui::Pos2,
-        pin_shape: PinShape,
-        pin_color: egui::Color32,
+        pin_shape: &PinShape,
+        state: PinShapeState,
         shape: egui::layers::ShapeIdx,
         ui: &mut egui::Ui,
     ) {
         let painter = ui.painter();
+        let (pin_pos, pin_color) = (state.pos, state.color);
         match pin_shape {
             PinShape::Circle => painter.set(
                 shape,
@@ -275,44 +369,24 @@ impl Style {
                 ),
             ),
             PinShape::Triangle => {
-                let sqrt_3 = 3f32.sqrt();
-                let left_offset = -0.166_666_7 * sqrt_3 * self.pin_triangle_side_length;
-                let right_offset = 0.333_333_3 * sqrt_3 * self.pin_triangle_side_length;
-                let verticacl_offset = 0.5 * self.pin_triangle_side_length;
+                let points = self.pin_triangle_points(pin_pos, state.side);
                 painter.set(
                     shape,
-                    egui::Shape::closed_line(
-                        vec![
-                            pin_pos + (left_offset, verticacl_offset).into(),
-                            pin_pos + (right_offset, 0.0).into(),
-                            pin_pos + (left_offset, -verticacl_offset).into(),
-                        ],
-                        (self.pin_line_thickness, pin_color),
-                    ),
+                    egui::Shape::closed_line(points, (self.pin_line_thickness, pin_color)),
                 )
             }
             PinShape::TriangleFilled => {
-                let sqrt_3 = 3f32.sqrt();
-                let left_offset = -0.166_666_7 * sqrt_3 * self.pin_triangle_side_length;
-                let right_offset = 0.333_333_3 * sqrt_3 * self.pin_triangle_side_length;
-                let verticacl_offset = 0.5 * self.pin_triangle_side_length;
+                let points = self.pin_triangle_points(pin_pos, state.side);
                 painter.set(
                     shape,
-                    egui::Shape::convex_polygon(
-                        vec![
-                            pin_pos + (left_offset, verticacl_offset).into(),
-                            pin_pos + (right_offset, 0.0).into(),
-                            pin_pos + (left_offset, -verticacl_offset).into(),
-                        ],
-                        pin_color,
-                        egui::Stroke::none(),
-                    ),
+                    egui::Shape::convex_polygon(points, pin_color, egui::Stroke::none()),
                 )
             }
+            PinShape::Custom(custom) => painter.set(shape, custom(&state, painter)),
         }
     }
 
-    pub(crate) fn format_node(&self, node: &mut NodeData, args: NodeArgs) {
+    pub(crate) fn format_node<T>(&self, node: &mut NodeData<T>, args: NodeArgs) {
         node.color_style.background =
             args.background.unwrap_or(self.colors[ColorStyle::NodeBackground as usize]);
         node.color_style.background_hovered = args
@@ -338,16 +412,27 @@ impl Style {
             args.border_thickness.unwrap_or(self.node_border_thickness);
     }
 
-    pub(crate) fn format_pin(&self, pin: &mut PinData, args: PinArgs, flags: usize) {
+    pub(crate) fn format_pin<T>(&self, pin: &mut PinData<T>, args: PinArgs, flags: usize) {
         pin.shape = args.shape;
+        pin.connected_shape = args.connected_shape;
         pin.flags = args.flags.unwrap_or(flags);
         pin.color_style.background =
             args.background.unwrap_or(self.colors[ColorStyle::Pin as usize]);
         pin.color_style.hovered =
             args.hovered.unwrap_or(self.colors[ColorStyle::PinHovered as usize]);
+        pin.color_style.connected =
+            args.connected.unwrap_or(self.colors[ColorStyle::PinConnected as usize]);
+        pin.port_type = args.port_type;
+        pin.label = args.label;
+        pin.side = args.side.unwrap_or(match pin.kind {
+            AttributeType::Input => PinSide::Left,
+            _ => PinSide::Right,
+        });
+        pin.side_offset = args.side_offset;
+        pin.hover_radius = args.hover_radius.unwrap_or(self.pin_hover_radius);
     }
 
-    pub(crate) fn format_link(&self, link: &mut LinkData, args: LinkArgs) {
+    pub(crate) fn format_link<T>(&self, link: &mut LinkData<T>, args: LinkArgs) {
         link.color_style.base = args.base.unwrap_or(self.colors[ColorStyle::Link as usize]);
         link.color_style.hovered =
             args.hovered.unwrap_or(self.colors[ColorStyle::LinkHovered as usize]);
@@ -0,0 +1,1692 @@
+//! Drives a Context inside a headless egui context with scripted input, for testing editor interactions.
+//! Enabled for the crate's own tests and by the `test-support` feature.
+//!
+//! ``` rust
+//! use egui_nodes::{testing::Harness, LinkArgs, NodeConstructor};
+//!
+//! let mut harness = Harness::new();
+//! let mut show = |ctx: &mut egui_nodes::Context, ui: &mut egui::Ui| {
+//!     let nodes = vec![
+//!         NodeConstructor::new(0, Default::default())
+//!             .with_origin([50.0, 50.0].into())
+//!             .with_output_attribute(0, Default::default(), |ui| ui.label("Output")),
+//!         NodeConstructor::new(1, Default::default())
+//!             .with_origin([250.0, 50.0].into())
+//!             .with_input_attribute(1, Default::default(), |ui| ui.label("Input")),
+//!     ];
+//!     ctx.show(nodes, Vec::<(usize, usize, usize, LinkArgs)>::new(), ui);
+//! };
+//! harness.run(&mut show);
+//!
+//! let (start, end) = (harness.pin_pos(0).unwrap(), harness.pin_pos(1).unwrap());
+//! harness.drag(egui::PointerButton::Primary, start, end, &mut show);
+//! assert_eq!(harness.ctx.link_created(), Some((0, 1, false)));
+//! ```
+
+use super::*;
+
+/// The size of the headless screen
+pub const SCREEN_SIZE: egui::Vec2 = egui::vec2(800.0, 600.0);
+
+/// Owns a Context and a headless egui context, and queues input for the next frame
+pub struct Harness<T = usize> {
+    pub ctx: Context<T>,
+    pub egui_ctx: egui::CtxRef,
+    events: Vec<egui::Event>,
+    modifiers: egui::Modifiers,
+    pointer_pos: egui::Pos2,
+    time: f64,
+}
+
+impl<T: Hash + Eq + Clone> Harness<T> {
+    pub fn new() -> Self {
+        Self {
+            ctx: Context::default(),
+            egui_ctx: egui::CtxRef::default(),
+            events: Vec::new(),
+            modifiers: Default::default(),
+            pointer_pos: egui::Pos2::ZERO,
+            time: 0.0,
+        }
+    }
+
+    /// Runs a single frame with the queued input. The editor fills the whole screen, so
+    /// screen space and editor space are the same
+    pub fn run(&mut self, show: impl FnOnce(&mut Context<T>, &mut egui::Ui)) {
+        self.time += 1.0 / 60.0;
+        let input = egui::RawInput {
+            screen_rect: Some(egui::Rect::from_min_size(egui::Pos2::ZERO, SCREEN_SIZE)),
+            time: Some(self.time),
+            modifiers: self.modifiers,
+            events: std::mem::take(&mut self.events),
+            ..Default::default()
+        };
+        let ctx = &mut self.ctx;
+        let _ = self.egui_ctx.run(input, |egui_ctx| {
+            egui::CentralPanel::default()
+                .frame(egui::Frame::none())
+                .show(egui_ctx, |ui| show(ctx, ui));
+        });
+    }
+
+    /// Runs frames until the layout of the editor has settled
+    pub fn settle(&mut self, mut show: impl FnMut(&mut Context<T>, &mut egui::Ui)) {
+        for _ in 0..3 {
+            self.run(&mut show);
+        }
+    }
+
+    pub fn pointer_move(&mut self, pos: egui::Pos2) {
+        self.pointer_pos = pos;
+        self.events.push(egui::Event::PointerMoved(pos));
+    }
+
+    pub fn pointer_press(&mut self, button: egui::PointerButton) {
+        self.pointer_button(button, true);
+    }
+
+    pub fn pointer_release(&mut self, button: egui::PointerButton) {
+        self.pointer_button(button, false);
+    }
+
+    fn pointer_button(&mut self, button: egui::PointerButton, pressed: bool) {
+        self.events.push(egui::Event::PointerButton {
+            pos: self.pointer_pos,
+            button,
+            pressed,
+            modifiers: self.modifiers,
+        });
+    }
+
+    pub fn key_press(&mut self, key: egui::Key) {
+        self.key(key, true);
+    }
+
+    pub fn key_release(&mut self, key: egui::Key) {
+        self.key(key, false);
+    }
+
+    fn key(&mut self, key: egui::Key, pressed: bool) {
+        self.events.push(egui::Event::Key {
+            key,
+            pressed,
+            modifiers: self.modifiers,
+        });
+    }
+
+    /// Types text into the focused widget
+    pub fn text(&mut self, text: &str) {
+        self.events.push(egui::Event::Text(text.to_string()));
+    }
+
+    /// The modifiers that are held for the following input
+    pub fn set_modifiers(&mut self, modifiers: egui::Modifiers) {
+        self.modifiers = modifiers;
+    }
+
+    /// Presses button at from, moves to to over a few frames and releases it there,
+    /// running one frame per step. The frame with the release is run last, so the events it
+    /// emits can be checked on the Context afterwards
+    pub fn drag(
+        &mut self,
+        button: egui::PointerButton,
+        from: egui::Pos2,
+        to: egui::Pos2,
+        mut show: impl FnMut(&mut Context<T>, &mut egui::Ui),
+    ) {
+        const STEPS: usize = 4;
+        self.pointer_move(from);
+        self.run(&mut show);
+        self.pointer_press(button);
+        self.run(&mut show);
+        for i in 1..=STEPS {
+            self.pointer_move(from + (to - from) * (i as f32 / STEPS as f32));
+            self.run(&mut show);
+        }
+        self.pointer_release(button);
+        self.run(&mut show);
+    }
+
+    /// Presses and releases button at pos
+    pub fn click(
+        &mut self,
+        button: egui::PointerButton,
+        pos: egui::Pos2,
+        mut show: impl FnMut(&mut Context<T>, &mut egui::Ui),
+    ) {
+        self.pointer_move(pos);
+        self.run(&mut show);
+        self.pointer_press(button);
+        self.run(&mut show);
+        self.pointer_release(button);
+        self.run(&mut show);
+    }
+
+    /// Where a pin was drawn in the last frame
+    pub fn pin_pos(&self, pin_id: T) -> Option<egui::Pos2> {
+        self.ctx.get_pin_pos_screen_space(pin_id)
+    }
+
+    /// Where a node was drawn in the last frame
+    pub fn node_rect(&self, node_id: T) -> Option<egui::Rect> {
+        self.ctx.get_node_rect_screen_space(node_id)
+    }
+}
+
+impl<T: Hash + Eq + Clone> Default for Harness<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use egui::PointerButton;
+
+    /// Three nodes, 0 -> 1 and 0 -> 2 can be linked. Applies link events to links like an app would
+    fn graph(ctx: &mut Context, links: &mut Vec<(usize, usize)>, ui: &mut egui::Ui) {
+        let nodes = vec![
+            NodeConstructor::new(0, Default::default())
+                .with_origin([50.0, 50.0].into())
+                .with_title(|ui| ui.label("Node A"))
+                .with_output_attribute(0, Default::default(), |ui| ui.label("Output")),
+            NodeConstructor::new(1, Default::default())
+                .with_origin([300.0, 50.0].into())
+                .with_title(|ui| ui.label("Node B"))
+                .with_input_attribute(1, Default::default(), |ui| ui.label("Input")),
+            NodeConstructor::new(2, Default::default())
+                .with_origin([300.0, 250.0].into())
+                .with_title(|ui| ui.label("Node C"))
+                .with_input_attribute(2, Default::default(), |ui| ui.label("Input")),
+        ];
+        ctx.show(
+            nodes,
+            links
+                .iter()
+                .enumerate()
+                .map(|(i, (start, end))| (i, *start, *end, LinkArgs::default())),
+            ui,
+        );
+        if let Some(idx) = ctx.link_destroyed() {
+            links.remove(idx);
+        }
+        if let Some((start, end, _)) = ctx.link_created() {
+            links.push((start, end));
+        }
+    }
+
+    fn setup(links: &mut Vec<(usize, usize)>) -> Harness {
+        let mut harness = Harness::new();
+        harness.settle(|ctx, ui| graph(ctx, links, ui));
+        harness
+    }
+
+    const EMPTY: egui::Pos2 = egui::pos2(600.0, 500.0);
+
+    #[test]
+    fn drag_between_pins_creates_link() {
+        let mut links = Vec::new();
+        let mut harness = setup(&mut links);
+        let (start, end) = (harness.pin_pos(0).unwrap(), harness.pin_pos(1).unwrap());
+        harness.drag(PointerButton::Primary, start, end, |ctx, ui| {
+            graph(ctx, &mut links, ui)
+        });
+        assert_eq!(harness.ctx.link_created(), Some((0, 1, false)));
+        assert_eq!(harness.ctx.link_created_node(), Some((0, 0, 1, 1, false)));
+        assert_eq!(links, vec![(0, 1)]);
+    }
+
+    #[test]
+    fn drag_from_input_reports_output_first() {
+        let mut links = Vec::new();
+        let mut harness = setup(&mut links);
+        let (start, end) = (harness.pin_pos(2).unwrap(), harness.pin_pos(0).unwrap());
+        harness.drag(PointerButton::Primary, start, end, |ctx, ui| {
+            graph(ctx, &mut links, ui)
+        });
+        assert_eq!(links, vec![(0, 2)]);
+    }
+
+    #[test]
+    fn dropping_on_canvas_reports_dropped_link() {
+        let mut links = Vec::new();
+        let mut harness = setup(&mut links);
+        let start = harness.pin_pos(0).unwrap();
+        harness.drag(PointerButton::Primary, start, EMPTY, |ctx, ui| {
+            graph(ctx, &mut links, ui)
+        });
+        assert_eq!(harness.ctx.link_dropped(false), Some(0));
+        assert_eq!(harness.ctx.link_created(), None);
+        assert!(links.is_empty());
+    }
+
+    #[test]
+    fn duplicate_links_are_not_created() {
+        let mut links = vec![(0, 1)];
+        let mut harness = setup(&mut links);
+        let (start, end) = (harness.pin_pos(0).unwrap(), harness.pin_pos(1).unwrap());
+        harness.drag(PointerButton::Primary, start, end, |ctx, ui| {
+            graph(ctx, &mut links, ui)
+        });
+        assert_eq!(harness.ctx.link_created(), None);
+        assert_eq!(links, vec![(0, 1)]);
+    }
+
+    #[test]
+    fn dragging_linked_pin_detaches_link() {
+        let mut links = vec![(0, 1)];
+        let mut harness = setup(&mut links);
+        harness.ctx.attribute_flag_push(AttributeFlags::EnableLinkDetachWithDragClick);
+        harness.settle(|ctx, ui| graph(ctx, &mut links, ui));
+
+        let start = harness.pin_pos(1).unwrap();
+        harness.pointer_move(start);
+        harness.run(|ctx, ui| graph(ctx, &mut links, ui));
+        harness.pointer_press(PointerButton::Primary);
+        harness.run(|ctx, ui| graph(ctx, &mut links, ui));
+        assert_eq!(harness.ctx.link_destroyed(), Some(0));
+        assert!(links.is_empty());
+
+        harness.pointer_move(EMPTY);
+        harness.run(|ctx, ui| graph(ctx, &mut links, ui));
+        harness.pointer_release(PointerButton::Primary);
+        harness.run(|ctx, ui| graph(ctx, &mut links, ui));
+        assert_eq!(harness.ctx.link_dropped(true), Some(0));
+        assert_eq!(harness.ctx.link_dropped(false), None);
+    }
+
+    #[test]
+    fn snapping_creates_link_before_release() {
+        let mut links = Vec::new();
+        let mut harness = setup(&mut links);
+        harness.ctx.attribute_flag_push(AttributeFlags::EnableLinkCreationOnSnap);
+        harness.settle(|ctx, ui| graph(ctx, &mut links, ui));
+
+        let (start, end) = (harness.pin_pos(0).unwrap(), harness.pin_pos(1).unwrap());
+        harness.pointer_move(start);
+        harness.run(|ctx, ui| graph(ctx, &mut links, ui));
+        harness.pointer_press(PointerButton::Primary);
+        harness.run(|ctx, ui| graph(ctx, &mut links, ui));
+        harness.pointer_move(end);
+        harness.run(|ctx, ui| graph(ctx, &mut links, ui));
+        assert_eq!(harness.ctx.link_created(), Some((0, 1, true)));
+        assert_eq!(links, vec![(0, 1)]);
+
+        // moving off the pin again detaches the snapped link
+        harness.pointer_move(EMPTY);
+        harness.run(|ctx, ui| graph(ctx, &mut links, ui));
+        assert_eq!(harness.ctx.link_destroyed(), Some(0));
+        assert!(links.is_empty());
+    }
+
+    #[test]
+    fn box_selection_selects_nodes_and_links() {
+        let mut links = vec![(0, 1)];
+        let mut harness = setup(&mut links);
+        let node_a = harness.node_rect(0).unwrap();
+        let node_b = harness.node_rect(1).unwrap();
+        harness.drag(
+            PointerButton::Primary,
+            node_a.left_top() - egui::vec2(20.0, 20.0),
+            node_b.right_bottom() + egui::vec2(20.0, 20.0),
+            |ctx, ui| graph(ctx, &mut links, ui),
+        );
+        let mut selected = harness.ctx.get_selected_nodes();
+        selected.sort_unstable();
+        assert_eq!(selected, vec![0, 1]);
+        assert_eq!(harness.ctx.num_selected_nodes(), 2);
+        assert_eq!(harness.ctx.get_selected_links(), vec![0]);
+    }
+
+    #[test]
+    fn dragging_node_moves_it() {
+        let mut links = Vec::new();
+        let mut harness = setup(&mut links);
+        let before = harness.ctx.get_node_pos_grid_space(0).unwrap();
+        let title = harness.node_rect(0).unwrap().left_top() + egui::vec2(10.0, 10.0);
+        let delta = egui::vec2(40.0, 30.0);
+        harness.drag(PointerButton::Primary, title, title + delta, |ctx, ui| {
+            graph(ctx, &mut links, ui)
+        });
+        assert_eq!(harness.ctx.get_node_pos_grid_space(0), Some(before + delta));
+        assert_eq!(harness.ctx.get_selected_nodes(), vec![0]);
+    }
+
+    #[test]
+    fn middle_drag_pans_canvas() {
+        let mut links = Vec::new();
+        let mut harness = setup(&mut links);
+        let before = harness.node_rect(0).unwrap();
+        let delta = egui::vec2(-50.0, 25.0);
+        harness.drag(PointerButton::Middle, EMPTY, EMPTY + delta, |ctx, ui| {
+            graph(ctx, &mut links, ui)
+        });
+        assert_eq!(harness.ctx.get_panning(), delta);
+        harness.run(|ctx, ui| graph(ctx, &mut links, ui));
+        assert_eq!(harness.node_rect(0).unwrap(), before.translate(delta));
+    }
+
+    #[test]
+    fn acyclic_mode_rejects_links_closing_a_cycle() {
+        let links = std::cell::RefCell::new(Vec::new());
+        let show = |ctx: &mut Context, ui: &mut egui::Ui| {
+            // node n has input pin 2n and output pin 2n + 1, the inout nodes have pins 10 and 11
+            let mut nodes = (0..3)
+                .map(|n| {
+                    NodeConstructor::new(n, Default::default())
+                        .with_origin([50.0 + 250.0 * n as f32, 50.0].into())
+                        .with_input_attribute(2 * n, Default::default(), |ui| ui.label("In"))
+                        .with_output_attribute(2 * n + 1, Default::default(), |ui| ui.label("Out"))
+                })
+                .collect::<Vec<_>>();
+            for n in 3..5 {
+                nodes.push(
+                    NodeConstructor::new(n, Default::default())
+                        .with_origin([50.0 + 250.0 * (n - 3) as f32, 300.0].into())
+                        .with_inout_attribute(n + 7, Default::default(), |ui| ui.label("InOut")),
+                );
+            }
+            ctx.set_acyclic(true);
+            let mut links = links.borrow_mut();
+            ctx.show(
+                nodes,
+                links
+                    .iter()
+                    .enumerate()
+                    .map(|(i, (start, end))| (i, *start, *end, LinkArgs::default())),
+                ui,
+            );
+            if let Some((start, end, _)) = ctx.link_created() {
+                links.push((start, end));
+            }
+        };
+        let mut harness = Harness::new();
+        harness.settle(show);
+        let drag = |harness: &mut Harness, start, end| {
+            let (start, end) = (
+                harness.pin_pos(start).unwrap(),
+                harness.pin_pos(end).unwrap(),
+            );
+            harness.drag(PointerButton::Primary, start, end, show);
+        };
+
+        // A -> B -> C, links between nodes that are not connected yet go through
+        drag(&mut harness, 1, 2);
+        assert_eq!(harness.ctx.link_created(), Some((1, 2, false)));
+        drag(&mut harness, 3, 4);
+        assert_eq!(*links.borrow(), vec![(1, 2), (3, 4)]);
+
+        // C -> A closes the cycle, the link is dropped instead
+        drag(&mut harness, 5, 0);
+        assert_eq!(harness.ctx.link_created(), None);
+        assert_eq!(harness.ctx.link_dropped(false), Some(5));
+
+        // a link dragged from the input of A to the output of C goes C -> A as well
+        drag(&mut harness, 0, 5);
+        assert_eq!(harness.ctx.link_dropped(false), Some(0));
+        assert_eq!(*links.borrow(), vec![(1, 2), (3, 4)]);
+
+        // C -> X, then a link dragged from the input of A to X goes X -> A and closes the cycle
+        drag(&mut harness, 5, 10);
+        drag(&mut harness, 0, 10);
+        assert_eq!(*links.borrow(), vec![(1, 2), (3, 4), (5, 10)]);
+
+        // links between inout pins go the way they were dragged, X -> Y is fine but Y -> C and Y -> A are not
+        drag(&mut harness, 10, 11);
+        drag(&mut harness, 11, 4);
+        drag(&mut harness, 11, 0);
+        assert_eq!(*links.borrow(), vec![(1, 2), (3, 4), (5, 10), (10, 11)]);
+        drag(&mut harness, 1, 11);
+        assert_eq!(
+            *links.borrow(),
+            vec![(1, 2), (3, 4), (5, 10), (10, 11), (1, 11)]
+        );
+    }
+
+    /// Two overlapping nodes that are pushed apart when dropped
+    fn overlapping_nodes(ctx: &mut Context, ui: &mut egui::Ui) {
+        let nodes = vec![
+            NodeConstructor::new(0, Default::default())
+                .with_origin([100.0, 100.0].into())
+                .with_title(|ui| ui.label("Node A"))
+                .with_output_attribute(0, Default::default(), |ui| ui.label("Output")),
+            NodeConstructor::new(1, Default::default())
+                .with_origin([130.0, 110.0].into())
+                .with_title(|ui| ui.label("Node B"))
+                .with_input_attribute(1, Default::default(), |ui| ui.label("Input")),
+        ];
+        ctx.set_resolve_overlaps_on_drop(true);
+        ctx.show(nodes, Vec::new(), ui);
+    }
+
+    #[test]
+    fn overlaps_are_resolved_after_dragging_not_clicking() {
+        let show = overlapping_nodes;
+        let mut harness = Harness::new();
+        harness.settle(show);
+        let title = harness.node_rect(1).unwrap().left_top() + egui::vec2(10.0, 10.0);
+
+        harness.click(PointerButton::Primary, title, show);
+        assert_eq!(harness.ctx.get_selected_nodes(), vec![1]);
+        assert_eq!(
+            harness.ctx.get_node_pos_grid_space(0),
+            Some([100.0, 100.0].into())
+        );
+        assert_eq!(
+            harness.ctx.get_node_pos_grid_space(1),
+            Some([130.0, 110.0].into())
+        );
+
+        let delta = egui::vec2(4.0, 0.0);
+        harness.drag(PointerButton::Primary, title, title + delta, show);
+        harness.run(show);
+        // the dropped node stays where it was put and the other one makes room
+        assert_eq!(
+            harness.ctx.get_node_pos_grid_space(1),
+            Some(egui::pos2(130.0, 110.0) + delta)
+        );
+        assert_ne!(
+            harness.ctx.get_node_pos_grid_space(0),
+            Some([100.0, 100.0].into())
+        );
+        let (a, b) = (harness.node_rect(0).unwrap(), harness.node_rect(1).unwrap());
+        assert!(!a.intersects(b));
+    }
+
+    #[test]
+    fn offscreen_nodes_are_culled_until_panned_back() {
+        let shown = std::cell::Cell::new(0);
+        let show = |ctx: &mut Context, ui: &mut egui::Ui| {
+            let nodes = vec![
+                NodeConstructor::new(0, Default::default())
+                    .with_origin([50.0, 50.0].into())
+                    .with_output_attribute(0, Default::default(), |ui| ui.label("Output")),
+                NodeConstructor::new(1, Default::default())
+                    .with_origin([1200.0, 50.0].into())
+                    .with_input_attribute(1, Default::default(), |ui| {
+                        shown.set(shown.get() + 1);
+                        ui.label("Input")
+                    }),
+            ];
+            ctx.show(nodes, vec![(0, 0, 1, LinkArgs::default())], ui);
+        };
+        let mut harness = Harness::new();
+        harness.settle(show);
+        let rect = harness.node_rect(1).unwrap();
+        let pin = harness.pin_pos(1).unwrap();
+        assert!(!rect.intersects(egui::Rect::from_min_size(egui::Pos2::ZERO, SCREEN_SIZE)));
+
+        // the node ui only runs in the first frame, before the node has a size
+        shown.set(0);
+        harness.run(show);
+        assert_eq!(shown.get(), 0);
+        assert_eq!(harness.ctx.culling_stats().nodes, 1);
+        assert_eq!(harness.ctx.culling_stats().links, 0);
+        assert_eq!(harness.ctx.pin_link_count(1), Some(1));
+        assert_eq!(harness.ctx.get_link_bezier(0).unwrap().3, pin);
+
+        // node 0 is panned off the screen instead
+        let (from, delta) = (egui::pos2(750.0, 500.0), egui::vec2(-600.0, 0.0));
+        harness.drag(PointerButton::Middle, from, from + delta, show);
+        harness.run(show);
+        assert!(shown.get() > 0);
+        assert_eq!(harness.ctx.culling_stats().nodes, 1);
+        assert_eq!(harness.node_rect(1), Some(rect.translate(delta)));
+        assert_eq!(harness.pin_pos(1), Some(pin + delta));
+        assert_eq!(harness.ctx.get_link_bezier(0).unwrap().3, pin + delta);
+    }
+
+    #[test]
+    fn links_between_offscreen_nodes_are_culled() {
+        let shown = std::cell::RefCell::new(Vec::new());
+        let show = |ctx: &mut Context, ui: &mut egui::Ui| {
+            // node n has input pin 2n and output pin 2n + 1, only node 0 is on the screen
+            let nodes = (0..3)
+                .map(|n| {
+                    let shown = &shown;
+                    NodeConstructor::new(n, Default::default())
+                        .with_origin([50.0 + 1000.0 * n as f32, 50.0].into())
+                        .with_input_attribute(2 * n, Default::default(), move |ui| {
+                            shown.borrow_mut().push(n);
+                            ui.label("In")
+                        })
+                        .with_output_attribute(2 * n + 1, Default::default(), |ui| ui.label("Out"))
+                })
+                .collect::<Vec<_>>();
+            let links = vec![
+                (0, 1, 2, LinkArgs::default()),
+                (1, 3, 4, LinkArgs::default()),
+            ];
+            ctx.show(nodes, links, ui);
+        };
+        let mut harness = Harness::new();
+        harness.settle(show);
+
+        shown.borrow_mut().clear();
+        harness.run(show);
+        assert_eq!(*shown.borrow(), vec![0]);
+        // the link from node 0 reaches into the screen, the one between nodes 1 and 2 does not
+        assert_eq!(harness.ctx.culling_stats().nodes, 2);
+        assert_eq!(harness.ctx.culling_stats().links, 1);
+
+        harness.ctx.set_viewport_culling(false);
+        shown.borrow_mut().clear();
+        harness.run(show);
+        assert_eq!(*shown.borrow(), vec![0, 1, 2]);
+        assert_eq!(harness.ctx.culling_stats().nodes, 0);
+        assert_eq!(harness.ctx.culling_stats().links, 0);
+    }
+
+    #[test]
+    fn culled_nodes_only_keep_the_pins_they_still_submit() {
+        let stage = std::cell::Cell::new(0);
+        let show = |ctx: &mut Context, ui: &mut egui::Ui| {
+            let mut culled = NodeConstructor::new(1, Default::default())
+                .with_origin([1200.0, 50.0].into())
+                .with_input_attribute(2, Default::default(), |ui| ui.label("Input"));
+            // the first input is removed while the node is off the screen
+            if stage.get() == 0 {
+                culled = culled.with_input_attribute(1, Default::default(), |ui| ui.label("Input"));
+            }
+            let mut nodes = vec![
+                NodeConstructor::new(0, Default::default())
+                    .with_origin([50.0, 50.0].into())
+                    .with_output_attribute(0, Default::default(), |ui| ui.label("Output")),
+                culled,
+            ];
+            // then another node takes over its id
+            if stage.get() == 2 {
+                nodes.push(
+                    NodeConstructor::new(2, Default::default())
+                        .with_origin([300.0, 50.0].into())
+                        .with_input_attribute(1, Default::default(), |ui| ui.label("Input")),
+                );
+            }
+            ctx.show(nodes, vec![(0, 0, 1, LinkArgs::default())], ui);
+        };
+        let mut harness = Harness::new();
+        harness.settle(show);
+        harness.run(show);
+        assert_eq!(harness.ctx.culling_stats().nodes, 1);
+        assert!(harness.ctx.diagnostics().is_empty());
+
+        stage.set(1);
+        harness.run(show);
+        assert_eq!(harness.ctx.culling_stats().nodes, 1);
+        assert_eq!(
+            harness.ctx.diagnostics(),
+            &[Diagnostic::DanglingLink { link: 0, pin: 1 }]
+        );
+        assert_eq!(harness.pin_pos(1), None);
+        assert!(harness.pin_pos(2).is_some());
+
+        stage.set(2);
+        harness.settle(show);
+        assert!(harness.ctx.diagnostics().is_empty());
+        assert_eq!(harness.ctx.pin_link_count(1), Some(1));
+        assert!(harness.node_rect(2).unwrap().contains(harness.pin_pos(1).unwrap()));
+    }
+
+    #[test]
+    fn nodes_are_kept_while_their_pins_reach_into_the_canvas() {
+        let show = |ctx: &mut Context, ui: &mut egui::Ui| {
+            let nodes: Vec<_> = (0..2)
+                .map(|x| {
+                    let args = PinArgs {
+                        side: Some(PinSide::Top),
+                        side_offset: Some(if x == 0 { 0.0 } else { 252.0 }),
+                        ..Default::default()
+                    };
+                    NodeConstructor::new(x, Default::default())
+                        .with_origin([-250.0, 50.0 + 200.0 * x as f32].into())
+                        .with_input_attribute(x, args, |ui| ui.label("Input"))
+                })
+                .collect();
+            ctx.show(nodes, vec![], ui);
+        };
+        let mut harness = Harness::new();
+        harness.settle(show);
+        for x in 0..2 {
+            assert!(harness.node_rect(x).unwrap().max.x < 0.0);
+        }
+        harness.run(show);
+        // only the pin of node 1 is placed far enough along its edge to reach into the canvas
+        assert_eq!(harness.ctx.culling_stats().nodes, 1);
+    }
+
+    #[test]
+    fn nodes_are_kept_while_their_pins_can_be_hovered_from_the_canvas() {
+        let show = |ctx: &mut Context, ui: &mut egui::Ui| {
+            let nodes: Vec<_> = (0..2)
+                .map(|x| {
+                    let args = PinArgs {
+                        hover_radius: Some(if x == 0 { 10.0 } else { 40.0 }),
+                        ..Default::default()
+                    };
+                    NodeConstructor::new(x, Default::default())
+                        .with_origin([830.0, 50.0 + 200.0 * x as f32].into())
+                        .with_input_attribute(x, args, |ui| ui.label("Input"))
+                })
+                .collect();
+            ctx.show(nodes, vec![], ui);
+        };
+        let mut harness = Harness::new();
+        harness.settle(show);
+        for x in 0..2 {
+            assert!(harness.node_rect(x).unwrap().min.x > SCREEN_SIZE.x);
+        }
+        harness.run(show);
+        // only the pin of node 1 can be hovered from inside the canvas
+        assert_eq!(harness.ctx.culling_stats().nodes, 1);
+    }
+
+    #[test]
+    fn selection_only_mode_does_not_resolve_overlaps() {
+        let show = overlapping_nodes;
+        let mut harness = Harness::new();
+        harness.settle(show);
+        harness.ctx.set_interaction_mode(InteractionMode::SelectionOnly);
+        let title = harness.node_rect(1).unwrap().left_top() + egui::vec2(10.0, 10.0);
+
+        harness.click(PointerButton::Primary, title, show);
+        harness.drag(
+            PointerButton::Primary,
+            title,
+            title + egui::vec2(4.0, 0.0),
+            show,
+        );
+        harness.run(show);
+        assert_eq!(harness.ctx.get_selected_nodes(), vec![1]);
+        assert_eq!(
+            harness.ctx.get_node_pos_grid_space(0),
+            Some([100.0, 100.0].into())
+        );
+        assert_eq!(
+            harness.ctx.get_node_pos_grid_space(1),
+            Some([130.0, 110.0].into())
+        );
+    }
+
+    #[test]
+    fn ids_can_be_scoped_per_node() {
+        use egui::Id;
+        let (source, sink) = (Id::new("source"), Id::new("sink"));
+        let mut show = |ctx: &mut Context<Id>, ui: &mut egui::Ui| {
+            let nodes = vec![
+                NodeConstructor::new(source, Default::default())
+                    .with_origin([50.0, 50.0].into())
+                    .with_output_attribute(source.with(0), Default::default(), |ui| {
+                        ui.label("Output")
+                    }),
+                NodeConstructor::new(sink, Default::default())
+                    .with_origin([300.0, 50.0].into())
+                    .with_input_attribute(sink.with(0), Default::default(), |ui| ui.label("Input")),
+            ];
+            ctx.show(nodes, Vec::new(), ui);
+        };
+        let mut harness = Harness::new();
+        harness.settle(&mut show);
+        let (start, end) = (
+            harness.pin_pos(source.with(0)).unwrap(),
+            harness.pin_pos(sink.with(0)).unwrap(),
+        );
+        harness.drag(PointerButton::Primary, start, end, &mut show);
+        assert_eq!(
+            harness.ctx.link_created_node(),
+            Some((source.with(0), source, sink.with(0), sink, false))
+        );
+    }
+
+    #[test]
+    fn duplicate_ids_are_reported() {
+        let mut show = |ctx: &mut Context, ui: &mut egui::Ui| {
+            let nodes = vec![
+                NodeConstructor::new(0, Default::default())
+                    .with_output_attribute(0, Default::default(), |ui| ui.label("Output"))
+                    .with_output_attribute(0, Default::default(), |ui| ui.label("Output")),
+                NodeConstructor::new(0, Default::default()).with_input_attribute(
+                    1,
+                    Default::default(),
+                    |ui| ui.label("Input"),
+                ),
+            ];
+            let links = vec![
+                (0, 0, 1, LinkArgs::default()),
+                (0, 0, 1, LinkArgs::default()),
+            ];
+            ctx.set_diagnostics_overlay(true);
+            ctx.show(nodes, links, ui);
+        };
+        let mut harness = Harness::new();
+        harness.settle(&mut show);
+        assert_eq!(
+            harness.ctx.diagnostics(),
+            &[
+                Diagnostic::DuplicateLink(0),
+                Diagnostic::DuplicateNode(0),
+                Diagnostic::DuplicatePin(0),
+                Diagnostic::DanglingLink { link: 0, pin: 1 },
+            ]
+        );
+    }
+
+    #[test]
+    fn dangling_links_are_skipped() {
+        let mut links = vec![(0, 7)];
+        let mut harness = setup(&mut links);
+        assert_eq!(
+            harness.ctx.diagnostics(),
+            &[Diagnostic::DanglingLink { link: 0, pin: 7 }]
+        );
+        assert_eq!(harness.pin_pos(7), None);
+
+        // the pin it starts from is still usable
+        let (start, end) = (harness.pin_pos(0).unwrap(), harness.pin_pos(1).unwrap());
+        harness.drag(PointerButton::Primary, start, end, |ctx, ui| {
+            graph(ctx, &mut links, ui)
+        });
+        assert_eq!(links, vec![(0, 7), (0, 1)]);
+    }
+
+    #[test]
+    fn editors_with_ids_do_not_share_ui_ids() {
+        let ids = std::cell::RefCell::new(Vec::new());
+        let mut other = Context::with_id(egui::Id::new("other"));
+        let mut harness = Harness::new();
+        harness.ctx.set_id(egui::Id::new("main"));
+        harness.run(|ctx, ui| {
+            for ctx in [ctx, &mut other] {
+                let nodes = (0..2).map(|id| {
+                    NodeConstructor::new(id, Default::default()).with_title(|ui| {
+                        ids.borrow_mut().push(ui.id());
+                        ui.label("Node")
+                    })
+                });
+                ctx.show(nodes, Vec::new(), ui);
+            }
+        });
+        let ids = ids.into_inner();
+        assert_eq!(ids.len(), 4);
+        assert_eq!(ids.iter().collect::<HashSet<_>>().len(), 4);
+    }
+
+    fn nodes<'a>() -> Vec<NodeConstructor<'a>> {
+        vec![
+            NodeConstructor::new(0, Default::default())
+                .with_origin([50.0, 50.0].into())
+                .with_title(|ui| ui.label("Node A"))
+                .with_output_attribute(0, Default::default(), |ui| ui.label("Output")),
+            NodeConstructor::new(1, Default::default())
+                .with_origin([300.0, 50.0].into())
+                .with_title(|ui| ui.label("Node B"))
+                .with_input_attribute(1, Default::default(), |ui| ui.label("Input")),
+        ]
+    }
+
+    #[test]
+    fn node_editor_reports_events() {
+        let mut events = Vec::new();
+        let mut show = |ctx: &mut Context, ui: &mut egui::Ui| {
+            let response = NodeEditor::new(ctx, nodes(), Vec::new()).show(ui);
+            events = response.events;
+        };
+        let mut harness = Harness::new();
+        harness.settle(&mut show);
+        let (start, end) = (harness.pin_pos(0).unwrap(), harness.pin_pos(1).unwrap());
+        harness.drag(PointerButton::Primary, start, end, &mut show);
+        assert_eq!(
+            events,
+            vec![NodeEditorEvent::LinkCreated {
+                start_pin: 0,
+                start_node: 0,
+                end_pin: 1,
+                end_node: 1,
+                created_from_snap: false,
+                start_slot: None,
+                end_slot: None,
+            }]
+        );
+    }
+
+    #[test]
+    fn read_only_node_editor_ignores_edits() {
+        let mut show = |ctx: &mut Context, ui: &mut egui::Ui| {
+            ui.add(NodeEditor::new(ctx, nodes(), Vec::new()).read_only(true));
+        };
+        let mut harness = Harness::new();
+        harness.settle(&mut show);
+        let (start, end) = (harness.pin_pos(0).unwrap(), harness.pin_pos(1).unwrap());
+        harness.drag(PointerButton::Primary, start, end, &mut show);
+        assert_eq!(harness.ctx.link_created(), None);
+
+        let before = harness.ctx.get_node_pos_grid_space(0);
+        let title = harness.node_rect(0).unwrap().left_top() + egui::vec2(10.0, 10.0);
+        harness.drag(
+            PointerButton::Primary,
+            title,
+            title + egui::vec2(40.0, 30.0),
+            &mut show,
+        );
+        assert_eq!(harness.ctx.get_node_pos_grid_space(0), before);
+        assert_eq!(harness.ctx.get_selected_nodes(), vec![0]);
+    }
+
+    #[test]
+    fn read_only_false_keeps_the_context_interaction_mode() {
+        let mut show = |ctx: &mut Context, ui: &mut egui::Ui| {
+            ui.add(NodeEditor::new(ctx, nodes(), Vec::new()).read_only(false));
+        };
+        let mut harness = Harness::new();
+        harness.ctx.set_interaction_mode(InteractionMode::ViewOnly);
+        harness.settle(&mut show);
+        let (start, end) = (harness.pin_pos(0).unwrap(), harness.pin_pos(1).unwrap());
+        harness.drag(PointerButton::Primary, start, end, &mut show);
+        assert_eq!(harness.ctx.link_created(), None);
+
+        let before = harness.ctx.get_node_pos_grid_space(0);
+        let title = harness.node_rect(0).unwrap().left_top() + egui::vec2(10.0, 10.0);
+        harness.drag(
+            PointerButton::Primary,
+            title,
+            title + egui::vec2(40.0, 30.0),
+            &mut show,
+        );
+        assert_eq!(harness.ctx.get_node_pos_grid_space(0), before);
+        assert_eq!(harness.ctx.interaction_mode(), InteractionMode::ViewOnly);
+    }
+
+    #[test]
+    fn clicking_minimap_pans_editor() {
+        let mut show = |ctx: &mut Context, ui: &mut egui::Ui| {
+            NodeEditor::new(ctx, nodes(), Vec::new()).minimap(true).show(ui);
+        };
+        let mut harness = Harness::new();
+        harness.settle(&mut show);
+        let minimap = egui::Rect::from_min_size(
+            (SCREEN_SIZE * 0.8 - egui::Vec2::splat(8.0)).to_pos2(),
+            SCREEN_SIZE * 0.2,
+        );
+        harness.click(
+            PointerButton::Primary,
+            minimap.left_top() + egui::vec2(2.0, 2.0),
+            &mut show,
+        );
+        let panning = harness.ctx.get_panning();
+        assert!(panning.x > 0.0 && panning.y > 0.0, "{:?}", panning);
+        assert!(harness.ctx.get_selected_nodes().is_empty());
+    }
+
+    #[test]
+    fn empty_minimap_does_not_pan() {
+        let mut show = |ctx: &mut Context, ui: &mut egui::Ui| {
+            NodeEditor::new(ctx, Vec::new(), Vec::new())
+                .size(egui::Vec2::ZERO)
+                .minimap(true)
+                .show(ui);
+        };
+        let mut harness = Harness::new();
+        harness.settle(&mut show);
+        let panning = harness.ctx.get_panning();
+        harness.ctx.minimap_pan_to(egui::Pos2::ZERO);
+        assert_eq!(harness.ctx.get_panning(), panning);
+    }
+
+    #[test]
+    fn view_only_mode_allows_panning_and_hovering() {
+        let mut links = vec![(0, 1)];
+        let mut harness = setup(&mut links);
+        harness.ctx.set_interaction_mode(InteractionMode::ViewOnly);
+
+        let node_a = harness.node_rect(0).unwrap();
+        let node_b = harness.node_rect(1).unwrap();
+        harness.drag(
+            PointerButton::Primary,
+            node_a.left_top() - egui::vec2(20.0, 20.0),
+            node_b.right_bottom() + egui::vec2(20.0, 20.0),
+            |ctx, ui| graph(ctx, &mut links, ui),
+        );
+        assert!(harness.ctx.get_selected_nodes().is_empty());
+        assert!(harness.ctx.get_selected_links().is_empty());
+
+        let title = node_a.left_top() + egui::vec2(10.0, 10.0);
+        harness.click(PointerButton::Primary, title, |ctx, ui| {
+            graph(ctx, &mut links, ui)
+        });
+        assert!(harness.ctx.get_selected_nodes().is_empty());
+        assert_eq!(harness.ctx.node_hovered(), Some(0));
+
+        let (start, end) = (harness.pin_pos(0).unwrap(), harness.pin_pos(2).unwrap());
+        harness.drag(PointerButton::Primary, start, end, |ctx, ui| {
+            graph(ctx, &mut links, ui)
+        });
+        assert_eq!(harness.ctx.link_started(), None);
+        assert_eq!(links, vec![(0, 1)]);
+
+        let delta = egui::vec2(-50.0, 25.0);
+        harness.drag(PointerButton::Middle, EMPTY, EMPTY + delta, |ctx, ui| {
+            graph(ctx, &mut links, ui)
+        });
+        assert_eq!(harness.ctx.get_panning(), delta);
+    }
+
+    #[test]
+    fn right_click_opens_matching_context_menu() {
+        let opened = std::cell::RefCell::new(Vec::new());
+        let mut show = |ctx: &mut Context, ui: &mut egui::Ui| {
+            NodeEditor::new(ctx, nodes(), Vec::new())
+                .canvas_context_menu(|ui, pos| {
+                    opened.borrow_mut().push(ContextMenuTarget::Canvas(pos));
+                    ui.label("Add node");
+                })
+                .node_context_menu(|ui, id| {
+                    opened.borrow_mut().push(ContextMenuTarget::Node(id));
+                    ui.label("Delete node");
+                })
+                .show(ui);
+        };
+        let mut harness = Harness::new();
+        harness.settle(&mut show);
+
+        let title = harness.node_rect(1).unwrap().left_top() + egui::vec2(10.0, 10.0);
+        harness.click(PointerButton::Secondary, title, &mut show);
+        assert_eq!(opened.borrow().last(), Some(&ContextMenuTarget::Node(1)));
+        assert_eq!(
+            harness.ctx.context_menu_target(),
+            Some(&ContextMenuTarget::Node(1))
+        );
+
+        // clicking elsewhere closes the menu
+        harness.click(PointerButton::Primary, EMPTY, &mut show);
+        opened.borrow_mut().clear();
+        harness.run(show);
+        assert!(opened.borrow().is_empty());
+
+        harness.click(PointerButton::Secondary, EMPTY, &mut show);
+        assert_eq!(
+            opened.borrow().last(),
+            Some(&ContextMenuTarget::Canvas(EMPTY))
+        );
+    }
+
+    fn palette() -> NodePalette<&'static str> {
+        NodePalette::new(vec![
+            PaletteEntry::new("constant", "Constant", "Input").with_output("f32"),
+            PaletteEntry::new("add", "Add", "Math")
+                .with_input("f32")
+                .with_input("f32")
+                .with_output("f32"),
+            PaletteEntry::new("not", "Not", "Logic").with_input("bool").with_output("bool"),
+            PaletteEntry::new("print", "Print", "Debug").with_input("bool").with_input("f32"),
+        ])
+    }
+
+    #[test]
+    fn dropping_link_opens_palette_with_compatible_entries() {
+        let mut palette = palette();
+        let mut show = |ctx: &mut Context, ui: &mut egui::Ui| {
+            let nodes = vec![NodeConstructor::new(0, Default::default())
+                .with_origin([50.0, 50.0].into())
+                .with_output_attribute(
+                    0,
+                    PinArgs {
+                        port_type: Some("f32".to_string()),
+                        ..Default::default()
+                    },
+                    |ui| ui.label("Output"),
+                )];
+            NodeEditor::new(ctx, nodes, Vec::new()).palette(&mut palette).show(ui);
+        };
+        let mut harness = Harness::new();
+        harness.settle(&mut show);
+        let start = harness.pin_pos(0).unwrap();
+        harness.drag(PointerButton::Primary, start, EMPTY, &mut show);
+
+        harness.text("p");
+        harness.run(&mut show);
+        harness.key_press(egui::Key::Enter);
+        harness.run(&mut show);
+        assert!(!palette.is_open());
+        assert_eq!(
+            palette.take_selection(),
+            Some(PaletteSelection {
+                entry: "print",
+                pin: Some(0),
+                port: Some(PortRef::Input(1)),
+                pos: EMPTY,
+            })
+        );
+    }
+
+    #[test]
+    fn space_opens_palette_with_keyboard_navigation() {
+        let palette = std::cell::RefCell::new(palette());
+        let show = |ctx: &mut Context, ui: &mut egui::Ui| {
+            NodeEditor::new(ctx, nodes(), Vec::new()).palette(&mut palette.borrow_mut()).show(ui);
+        };
+        let mut harness = Harness::new();
+        harness.settle(show);
+        harness.pointer_move(EMPTY);
+        harness.run(show);
+        harness.key_press(egui::Key::Space);
+        harness.run(show);
+        assert!(palette.borrow().is_open());
+
+        harness.key_press(egui::Key::Escape);
+        harness.run(show);
+        assert!(!palette.borrow().is_open());
+        // the popup covers the canvas until the next frame
+        harness.run(show);
+
+        harness.key_press(egui::Key::Space);
+        harness.run(show);
+        assert!(palette.borrow().is_open());
+        harness.text("ad");
+        harness.run(show);
+        harness.key_press(egui::Key::ArrowDown);
+        harness.key_press(egui::Key::Enter);
+        harness.run(show);
+        assert_eq!(
+            palette.borrow_mut().take_selection().map(|x| (x.entry, x.pin, x.port)),
+            Some(("add", None, None))
+        );
+
+        // without a filter the entries are sorted by category
+        harness.run(show);
+        harness.key_press(egui::Key::Space);
+        harness.run(show);
+        harness.key_press(egui::Key::ArrowDown);
+        harness.run(show);
+        harness.key_press(egui::Key::Enter);
+        harness.run(show);
+        assert_eq!(
+            palette.borrow_mut().take_selection().unwrap().entry,
+            "constant"
+        );
+    }
+
+    #[test]
+    fn dropping_drag_source_on_canvas_reports_payload() {
+        #[derive(Debug, PartialEq)]
+        struct Template(&'static str);
+
+        let dropped = std::cell::Cell::new(None);
+        let show = |ctx: &mut Context, ui: &mut egui::Ui| {
+            drag_source(ui, egui::Id::new("template"), Template("add"), |ui| {
+                ui.label("Add");
+            });
+            ctx.show(nodes(), Vec::new(), ui);
+            dropped.set(ctx.dropped_payload::<Template>().map(|(template, pos)| (template.0, pos)));
+            assert!(ctx.dropped_payload::<String>().is_none());
+        };
+        let mut harness = Harness::new();
+        harness.settle(show);
+        harness.drag(PointerButton::Primary, egui::pos2(10.0, 5.0), EMPTY, show);
+        let pos = harness.ctx.screen_space_to_grid_space(EMPTY);
+        assert_eq!(dropped.get(), Some(("add", pos)));
+        assert!(pos.y < EMPTY.y);
+        assert_eq!(harness.ctx.grid_space_to_screen_space(pos), EMPTY);
+
+        // the payload is only reported once
+        harness.click(PointerButton::Primary, EMPTY, show);
+        assert_eq!(dropped.get(), None);
+    }
+
+    #[test]
+    fn payloads_are_only_taken_by_the_canvas_they_are_dropped_on() {
+        let dropped = std::cell::RefCell::new(Vec::new());
+        let other = std::cell::RefCell::new(Context::with_id(egui::Id::new("other")));
+        let show = |ctx: &mut Context, ui: &mut egui::Ui| {
+            drag_source(ui, egui::Id::new("template"), "add", |ui| {
+                ui.label("Add");
+            });
+            ui.columns(2, |columns| {
+                let mut other = other.borrow_mut();
+                for (i, ctx) in [ctx, &mut *other].iter_mut().enumerate() {
+                    ctx.show(Vec::new(), Vec::new(), &mut columns[i]);
+                    if ctx.dropped_payload::<&str>().is_some() {
+                        dropped.borrow_mut().push(i);
+                    }
+                }
+            });
+        };
+        let mut harness = Harness::new();
+        harness.ctx.set_id(egui::Id::new("main"));
+        harness.settle(show);
+        let source = egui::pos2(10.0, 5.0);
+
+        harness.drag(
+            PointerButton::Primary,
+            source,
+            egui::pos2(600.0, 300.0),
+            show,
+        );
+        assert_eq!(*dropped.borrow(), vec![1]);
+
+        // dropped outside of both editors, the payload is not reported by a later click
+        dropped.borrow_mut().clear();
+        harness.drag(
+            PointerButton::Primary,
+            source,
+            source + egui::vec2(0.0, 2.0),
+            show,
+        );
+        harness.run(show);
+        harness.click(PointerButton::Primary, egui::pos2(200.0, 300.0), show);
+        assert!(dropped.borrow().is_empty());
+    }
+
+    #[test]
+    fn geometry_of_the_last_frame_can_be_queried() {
+        let mut links = vec![(0, 1)];
+        let harness = setup(&mut links);
+        let ctx = &harness.ctx;
+
+        let mut nodes = ctx.node_ids().copied().collect::<Vec<_>>();
+        nodes.sort_unstable();
+        assert_eq!(nodes, vec![0, 1, 2]);
+        assert_eq!(ctx.pin_ids().count(), 3);
+        assert_eq!(ctx.link_ids().collect::<Vec<_>>(), vec![&0]);
+
+        let node = ctx.get_node_rect_screen_space(0).unwrap();
+        let title = ctx.get_node_title_rect_screen_space(0).unwrap();
+        assert_eq!(title.min, node.min);
+        assert!(title.height() < node.height());
+        assert_eq!(ctx.get_pin_kind(0), Some(AttributeType::Output));
+        assert_eq!(ctx.get_pin_kind(1), Some(AttributeType::Input));
+
+        let bezier = ctx.get_link_bezier(0).unwrap();
+        assert_eq!(bezier.0, ctx.get_pin_pos_screen_space(0).unwrap());
+        assert_eq!(bezier.3, ctx.get_pin_pos_screen_space(1).unwrap());
+        assert!(bezier.1.x > bezier.0.x && bezier.2.x < bezier.3.x);
+        assert_eq!(ctx.get_link_bezier(1), None);
+    }
+
+    #[test]
+    fn hovering_pin_shows_its_tooltip() {
+        // the tooltip borrows app state for the frame it is shown in
+        let value = 1.5f32;
+        let shown = std::cell::Cell::new(0);
+        let show = |ctx: &mut Context, ui: &mut egui::Ui| {
+            let args = PinArgs::new().with_label("f32").with_tooltip(|ui| {
+                shown.set(shown.get() + 1);
+                ui.label(value.to_string());
+            });
+            let nodes = vec![NodeConstructor::new(0, Default::default())
+                .with_origin([50.0, 50.0].into())
+                .with_output_attribute(0, args, |ui| ui.label("Output"))];
+            ctx.show(nodes, Vec::new(), ui);
+        };
+        let mut harness = Harness::new();
+        harness.settle(show);
+        assert_eq!(shown.get(), 0);
+
+        harness.pointer_move(harness.pin_pos(0).unwrap());
+        harness.settle(show);
+        assert!(shown.get() > 0);
+        assert_eq!(harness.ctx.pin_hovered(), Some(0));
+
+        shown.set(0);
+        harness.pointer_move(EMPTY);
+        harness.settle(show);
+        assert_eq!(shown.get(), 0);
+    }
+
+    #[test]
+    fn pins_can_be_placed_on_any_side() {
+        let show = |ctx: &mut Context, ui: &mut egui::Ui| {
+            let side = |side, side_offset| PinArgs {
+                side: Some(side),
+                side_offset,
+                ..Default::default()
+            };
+            let nodes = vec![
+                NodeConstructor::new(0, Default::default())
+                    .with_origin([50.0, 50.0].into())
+                    .with_output_attribute(0, side(PinSide::Bottom, None), |ui| ui.label("Output")),
+                NodeConstructor::new(1, Default::default())
+                    .with_origin([50.0, 250.0].into())
+                    .with_input_attribute(1, side(PinSide::Top, Some(10.0)), |ui| {
+                        ui.label("Input")
+                    }),
+            ];
+            ctx.show(nodes, vec![(0, 0, 1, LinkArgs::default())], ui);
+        };
+        let mut harness = Harness::new();
+        harness.settle(show);
+
+        let (output, input) = (harness.pin_pos(0).unwrap(), harness.pin_pos(1).unwrap());
+        let (node_a, node_b) = (harness.node_rect(0).unwrap(), harness.node_rect(1).unwrap());
+        assert!(output.y >= node_a.max.y);
+        assert!(node_a.x_range().contains(&output.x));
+        assert!(input.y <= node_b.min.y);
+        assert_eq!(input.x, node_b.min.x + 10.0);
+
+        let bezier = harness.ctx.get_link_bezier(0).unwrap();
+        assert_eq!(bezier.1.x, output.x);
+        assert!(bezier.1.y > output.y);
+        assert_eq!(bezier.2.x, input.x);
+        assert!(bezier.2.y < input.y);
+    }
+
+    #[test]
+    fn inout_pins_link_to_any_pin() {
+        let mut links = Vec::new();
+        let mut show = |ctx: &mut Context, ui: &mut egui::Ui| {
+            let nodes = vec![
+                NodeConstructor::new(0, Default::default())
+                    .with_origin([50.0, 50.0].into())
+                    .with_inout_attribute(0, Default::default(), |ui| ui.label("A")),
+                NodeConstructor::new(1, Default::default())
+                    .with_origin([300.0, 50.0].into())
+                    .with_inout_attribute(1, Default::default(), |ui| ui.label("B")),
+                NodeConstructor::new(2, Default::default())
+                    .with_origin([50.0, 250.0].into())
+                    .with_output_attribute(2, Default::default(), |ui| ui.label("Output")),
+            ];
+            ctx.show(
+                nodes,
+                links
+                    .iter()
+                    .enumerate()
+                    .map(|(i, (start, end))| (i, *start, *end, LinkArgs::default())),
+                ui,
+            );
+            if let Some((start, end, _)) = ctx.link_created() {
+                links.push((start, end));
+            }
+        };
+        let mut harness = Harness::new();
+        harness.settle(&mut show);
+        let pin = |harness: &Harness, id| harness.pin_pos(id).unwrap();
+
+        // between two inout pins links go the way they were dragged
+        harness.drag(
+            PointerButton::Primary,
+            pin(&harness, 1),
+            pin(&harness, 0),
+            &mut show,
+        );
+        // dragging the other way is a duplicate
+        harness.drag(
+            PointerButton::Primary,
+            pin(&harness, 0),
+            pin(&harness, 1),
+            &mut show,
+        );
+        // outputs stay first
+        harness.drag(
+            PointerButton::Primary,
+            pin(&harness, 0),
+            pin(&harness, 2),
+            &mut show,
+        );
+        harness.run(&mut show);
+        assert_eq!(links, vec![(1, 0), (2, 0)]);
+        assert_eq!(harness.ctx.get_pin_kind(0), Some(AttributeType::InOut));
+    }
+
+    type SlotPin = (usize, usize);
+    const CONCAT: SlotPin = (100, 0);
+
+    /// A concat node with a variadic input linked to the outputs in inputs, and outputs nodes with an output (10 + i, 0).
+    /// The links are identified by the slot pin they end at
+    fn concat_graph(
+        ctx: &mut Context<SlotPin>,
+        ui: &mut egui::Ui,
+        inputs: &mut Vec<SlotPin>,
+        outputs: usize,
+    ) {
+        let detach = |_| PinArgs {
+            flags: Some(AttributeFlags::EnableLinkDetachWithDragClick as usize),
+            ..Default::default()
+        };
+        let mut nodes = vec![NodeConstructor::new((0, 0), Default::default())
+            .with_origin([300.0, 50.0].into())
+            .with_title(|ui| ui.label("Concat"))
+            .with_variadic_attribute(PinKind::Input, CONCAT, inputs.len(), detach, |ui, slot| {
+                ui.label(format!("Item {}", slot))
+            })];
+        for x in 0..outputs {
+            nodes.push(
+                NodeConstructor::new((1 + x, 0), Default::default())
+                    .with_origin([50.0, 50.0 + 150.0 * x as f32].into())
+                    .with_output_attribute((10 + x, 0), Default::default(), |ui| ui.label("Out")),
+            );
+        }
+        let links = inputs
+            .iter()
+            .enumerate()
+            .map(|(slot, start)| {
+                (
+                    CONCAT.slot_id(slot),
+                    *start,
+                    CONCAT.slot_id(slot),
+                    LinkArgs::default(),
+                )
+            })
+            .collect::<Vec<_>>();
+        let response = NodeEditor::new(ctx, nodes, links).show(ui);
+        for event in response.events {
+            match event {
+                NodeEditorEvent::LinkCreated {
+                    start_pin,
+                    end_slot: Some(slot),
+                    ..
+                } if slot == inputs.len() => inputs.push(start_pin),
+                NodeEditorEvent::VariadicSlotRemoved { attribute, slot } => {
+                    assert_eq!(attribute, CONCAT);
+                    inputs.remove(slot);
+                }
+                _ => (),
+            }
+        }
+    }
+
+    #[test]
+    fn variadic_attributes_grow_and_collapse() {
+        let inputs = std::cell::RefCell::new(Vec::new());
+        let show = |ctx: &mut Context<SlotPin>, ui: &mut egui::Ui| {
+            concat_graph(ctx, ui, &mut inputs.borrow_mut(), 2)
+        };
+        let mut harness = Harness::new();
+        harness.settle(show);
+        assert_eq!(harness.ctx.get_pin_slot(CONCAT.slot_id(0)), Some(0));
+        assert_eq!(harness.ctx.get_pin_slot(CONCAT.slot_id(1)), None);
+
+        let (a, free) = (
+            harness.pin_pos((10, 0)).unwrap(),
+            harness.pin_pos(CONCAT.slot_id(0)).unwrap(),
+        );
+        harness.drag(PointerButton::Primary, a, free, show);
+        harness.settle(show);
+        let (b, free) = (
+            harness.pin_pos((11, 0)).unwrap(),
+            harness.pin_pos(CONCAT.slot_id(1)).unwrap(),
+        );
+        harness.drag(PointerButton::Primary, b, free, show);
+        harness.settle(show);
+        assert_eq!(*inputs.borrow(), vec![(10, 0), (11, 0)]);
+        assert_eq!(harness.ctx.get_pin_slot(CONCAT.slot_id(2)), Some(2));
+
+        // detaching the first slot moves the second one up
+        let first = harness.pin_pos(CONCAT.slot_id(0)).unwrap();
+        harness.drag(PointerButton::Primary, first, EMPTY, show);
+        harness.settle(show);
+        assert_eq!(*inputs.borrow(), vec![(11, 0)]);
+        assert_eq!(harness.ctx.get_pin_slot(CONCAT.slot_id(1)), Some(1));
+        assert_eq!(harness.ctx.get_pin_slot(CONCAT.slot_id(2)), None);
+        let link = harness.ctx.get_link_bezier(CONCAT.slot_id(0)).unwrap();
+        assert_eq!(link.0, harness.pin_pos((11, 0)).unwrap());
+        assert_eq!(link.3, harness.pin_pos(CONCAT.slot_id(0)).unwrap());
+    }
+
+    #[test]
+    fn destroying_a_middle_variadic_link_moves_later_links_down() {
+        let inputs = std::cell::RefCell::new(Vec::new());
+        let show = |ctx: &mut Context<SlotPin>, ui: &mut egui::Ui| {
+            concat_graph(ctx, ui, &mut inputs.borrow_mut(), 3)
+        };
+        let mut harness = Harness::new();
+        harness.settle(show);
+        for x in 0..3 {
+            let (output, free) = (
+                harness.pin_pos((10 + x, 0)).unwrap(),
+                harness.pin_pos(CONCAT.slot_id(x)).unwrap(),
+            );
+            harness.drag(PointerButton::Primary, output, free, show);
+            harness.settle(show);
+        }
+        assert_eq!(*inputs.borrow(), vec![(10, 0), (11, 0), (12, 0)]);
+
+        let middle = harness.pin_pos(CONCAT.slot_id(1)).unwrap();
+        harness.drag(PointerButton::Primary, middle, EMPTY, show);
+        harness.settle(show);
+        assert_eq!(*inputs.borrow(), vec![(10, 0), (12, 0)]);
+        for (slot, output) in [(0, 10), (1, 12)].iter() {
+            let pin = CONCAT.slot_id(*slot);
+            let link = harness.ctx.get_link_bezier(pin).unwrap();
+            assert_eq!(link.0, harness.pin_pos((*output, 0)).unwrap());
+            assert_eq!(link.3, harness.pin_pos(pin).unwrap());
+            assert_eq!(harness.ctx.pin_link_count(pin), Some(1));
+        }
+        // the free slot stays last
+        assert_eq!(harness.ctx.get_pin_slot(CONCAT.slot_id(2)), Some(2));
+        assert_eq!(harness.ctx.pin_link_count(CONCAT.slot_id(2)), Some(0));
+        assert_eq!(harness.ctx.get_pin_slot(CONCAT.slot_id(3)), None);
+        assert!(harness.ctx.get_link_bezier(CONCAT.slot_id(2)).is_none());
+    }
+
+    #[test]
+    fn variadic_attributes_can_share_the_first_id_part() {
+        let mut show = |ctx: &mut Context<SlotPin>, ui: &mut egui::Ui| {
+            let nodes = vec![NodeConstructor::new((0, 0), Default::default())
+                .with_variadic_attribute(
+                    PinKind::Input,
+                    (100, 0),
+                    1,
+                    |_| PinArgs::default(),
+                    |ui, _| ui.label("A"),
+                )
+                .with_variadic_attribute(
+                    PinKind::Input,
+                    (100, 1),
+                    1,
+                    |_| PinArgs::default(),
+                    |ui, _| ui.label("B"),
+                )
+                .with_input_attribute((100, 2), Default::default(), |ui| ui.label("C"))];
+            ctx.show(nodes, Vec::new(), ui);
+        };
+        let mut harness = Harness::new();
+        harness.settle(&mut show);
+        assert!(harness.ctx.diagnostics().is_empty());
+        for slot in 0..2 {
+            assert_eq!(harness.ctx.get_pin_slot((100, 0).slot_id(slot)), Some(slot));
+            assert_eq!(harness.ctx.get_pin_slot((100, 1).slot_id(slot)), Some(slot));
+        }
+        assert_eq!(harness.ctx.get_pin_slot((100, 2)), None);
+        assert!(harness.pin_pos((100, 2)).is_some());
+    }
+
+    #[test]
+    fn variadic_attributes_derive_usize_pin_ids() {
+        let inputs = std::cell::RefCell::new(Vec::new());
+        let show = |ctx: &mut Context, ui: &mut egui::Ui| {
+            let mut inputs = inputs.borrow_mut();
+            let nodes = vec![
+                NodeConstructor::new(0, Default::default())
+                    .with_origin([50.0, 50.0].into())
+                    .with_output_attribute(0, Default::default(), |ui| ui.label("Output")),
+                NodeConstructor::new(1, Default::default())
+                    .with_origin([300.0, 50.0].into())
+                    .with_variadic_attribute(
+                        PinKind::Input,
+                        1,
+                        inputs.len(),
+                        |_| PinArgs::default(),
+                        |ui, slot| ui.label(format!("Item {}", slot)),
+                    ),
+            ];
+            let links = inputs
+                .iter()
+                .enumerate()
+                .map(|(slot, start)| (slot, *start, 1usize.slot_id(slot), LinkArgs::default()))
+                .collect::<Vec<_>>();
+            ctx.show(nodes, links, ui);
+            if let Some((start, _, _)) = ctx.link_created() {
+                inputs.push(start);
+            }
+        };
+        let mut harness = Harness::new();
+        harness.settle(show);
+        let free = 1usize.slot_id(0);
+        // slot pins stay clear of the ids the app hands out
+        assert!(free > 1 && 1usize.slot_id(1) != free);
+        assert_eq!(harness.ctx.get_pin_slot(free), Some(0));
+
+        let (start, end) = (harness.pin_pos(0).unwrap(), harness.pin_pos(free).unwrap());
+        harness.drag(PointerButton::Primary, start, end, show);
+        harness.settle(show);
+        assert_eq!(*inputs.borrow(), vec![0]);
+        assert_eq!(harness.ctx.get_pin_slot(1usize.slot_id(1)), Some(1));
+    }
+
+    #[test]
+    fn custom_pin_shapes_and_hover_radius() {
+        use std::sync::{Arc, Mutex};
+
+        let drawn = Arc::new(Mutex::new(Vec::new()));
+        let show = |ctx: &mut Context, ui: &mut egui::Ui| {
+            let drawn = drawn.clone();
+            let diamond = PinShape::custom(move |state, _| {
+                drawn.lock().unwrap().push((state.pos, state.hovered, state.connected));
+                let r = 6.0;
+                egui::Shape::convex_polygon(
+                    vec![
+                        state.pos + egui::vec2(0.0, -r),
+                        state.pos + egui::vec2(r, 0.0),
+                        state.pos + egui::vec2(0.0, r),
+                        state.pos + egui::vec2(-r, 0.0),
+                    ],
+                    state.color,
+                    egui::Stroke::none(),
+                )
+            });
+            let nodes = vec![
+                NodeConstructor::new(0, Default::default())
+                    .with_origin([50.0, 107.0].into())
+                    .with_output_attribute(
+                        0,
+                        PinArgs {
+                            shape: diamond,
+                            hover_radius: Some(30.0),
+                            ..Default::default()
+                        },
+                        |ui| ui.label("Output"),
+                    ),
+                NodeConstructor::new(1, Default::default())
+                    .with_origin([300.0, 107.0].into())
+                    .with_input_attribute(1, Default::default(), |ui| ui.label("Input")),
+                NodeConstructor::new(2, Default::default())
+                    .with_origin([300.0, 250.0].into())
+                    .with_input_attribute(2, Default::default(), |ui| ui.label("Input")),
+            ];
+            ctx.show(nodes, vec![(0, 0, 1, LinkArgs::default())], ui);
+        };
+        let mut harness = Harness::new();
+        harness.settle(show);
+        let output = harness.pin_pos(0).unwrap();
+        assert_eq!(drawn.lock().unwrap().last(), Some(&(output, false, true)));
+
+        harness.pointer_move(output + egui::vec2(20.0, 0.0));
+        harness.settle(show);
+        assert_eq!(harness.ctx.pin_hovered(), Some(0));
+        assert_eq!(drawn.lock().unwrap().last(), Some(&(output, true, true)));
+
+        // the link is hovered through anywhere its pin is, even outside the link's grid cells
+        harness.pointer_move(output + egui::vec2(0.0, 25.0));
+        harness.settle(show);
+        assert_eq!(harness.ctx.pin_hovered(), Some(0));
+        assert_eq!(harness.ctx.link_hovered(), Some(0));
+
+        harness.pointer_move(harness.pin_pos(2).unwrap() + egui::vec2(-20.0, 0.0));
+        harness.settle(show);
+        assert_eq!(harness.ctx.pin_hovered(), None);
+    }
+
+    #[test]
+    fn connected_pins_use_connected_shape_and_color() {
+        use std::sync::{Arc, Mutex};
+
+        let mut links = vec![(0, 1)];
+        let harness = setup(&mut links);
+        assert_eq!(harness.ctx.pin_link_count(0), Some(1));
+        assert_eq!(harness.ctx.pin_link_count(1), Some(1));
+        assert_eq!(harness.ctx.pin_link_count(2), Some(0));
+        assert_eq!(harness.ctx.pin_link_count(3), None);
+
+        let drawn = Arc::new(Mutex::new(Vec::new()));
+        let (background, connected) = (egui::Color32::RED, egui::Color32::GREEN);
+        let show = |linked: bool| {
+            let drawn = &drawn;
+            move |ctx: &mut Context, ui: &mut egui::Ui| {
+                let shape = |connected_shape: bool| {
+                    let drawn = drawn.clone();
+                    PinShape::custom(move |state, _| {
+                        drawn.lock().unwrap().push((connected_shape, state.color));
+                        egui::Shape::circle_filled(state.pos, 4.0, state.color)
+                    })
+                };
+                let args = || PinArgs {
+                    shape: shape(false),
+                    connected_shape: Some(shape(true)),
+                    background: Some(background),
+                    connected: Some(connected),
+                    ..Default::default()
+                };
+                let nodes = vec![
+                    NodeConstructor::new(0, Default::default())
+                        .with_origin([50.0, 50.0].into())
+                        .with_output_attribute(0, args(), |ui| ui.label("Output")),
+                    NodeConstructor::new(1, Default::default())
+                        .with_origin([300.0, 50.0].into())
+                        .with_input_attribute(1, args(), |ui| ui.label("Input")),
+                ];
+                let links = if linked {
+                    vec![(0, 0, 1, LinkArgs::default())]
+                } else {
+                    Vec::new()
+                };
+                ctx.show(nodes, links, ui);
+            }
+        };
+
+        let mut harness = Harness::new();
+        harness.run(show(false));
+        assert_eq!(*drawn.lock().unwrap(), vec![(false, background); 2]);
+        drawn.lock().unwrap().clear();
+        harness.run(show(true));
+        assert_eq!(*drawn.lock().unwrap(), vec![(true, connected); 2]);
+    }
+
+    #[test]
+    fn hovered_color_takes_precedence_over_connected() {
+        use std::sync::{Arc, Mutex};
+
+        let drawn = Arc::new(Mutex::new(Vec::new()));
+        let show = |ctx: &mut Context, ui: &mut egui::Ui| {
+            let drawn = drawn.clone();
+            let ring = PinShape::custom(move |state, _| {
+                drawn.lock().unwrap().push((state.color, state.hovered, state.connected));
+                egui::Shape::circle_stroke(state.pos, 4.0, (1.0, state.color))
+            });
+            let args = PinArgs {
+                connected_shape: Some(ring),
+                background: Some(egui::Color32::RED),
+                hovered: Some(egui::Color32::GREEN),
+                connected: Some(egui::Color32::BLUE),
+                ..Default::default()
+            };
+            let nodes = vec![
+                NodeConstructor::new(0, Default::default())
+                    .with_origin([50.0, 50.0].into())
+                    .with_output_attribute(0, args, |ui| ui.label("Output")),
+                NodeConstructor::new(1, Default::default())
+                    .with_origin([300.0, 50.0].into())
+                    .with_input_attribute(1, Default::default(), |ui| ui.label("Input")),
+            ];
+            ctx.show(nodes, vec![(0, 0, 1, LinkArgs::default())], ui);
+        };
+        let mut harness = Harness::new();
+        harness.settle(show);
+        let last = || *drawn.lock().unwrap().last().unwrap();
+        assert_eq!(last(), (egui::Color32::BLUE, false, true));
+
+        // the connected shape is kept, only the color changes
+        harness.pointer_move(harness.pin_pos(0).unwrap());
+        harness.settle(show);
+        assert_eq!(harness.ctx.pin_hovered(), Some(0));
+        assert_eq!(last(), (egui::Color32::GREEN, true, true));
+    }
+}